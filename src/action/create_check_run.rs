@@ -32,7 +32,7 @@ impl<'a> Action<CreateCheckRunInput, CheckRun, CreateCheckRunError> for CreateCh
 
         let check_run = self
             .github_client
-            .post(&url, Some(input))
+            .post_with_options(&url, Some(input), &self.request_options())
             .await
             .context("failed to create check run")?;
 
@@ -16,6 +16,7 @@ pub enum GetFileResponse {
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub(super) enum GetFilePayload {
+    #[serde(rename = "dir")]
     Directory,
     File(FilePayload),
     Submodule,
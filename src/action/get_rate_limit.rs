@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use derive_new::new;
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+
+use crate::action::Action;
+use crate::github::client::{GitHubClient, RateLimit, RateLimitResource};
+
+#[derive(Debug, new)]
+pub struct GetRateLimit<'a> {
+    github_client: &'a GitHubClient,
+}
+
+/// The rate limits of every resource, as returned by `GET /rate_limit`.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize, Getters)]
+pub struct RateLimits {
+    #[getset(get = "pub")]
+    resources: HashMap<RateLimitResource, RateLimit>,
+}
+
+impl RateLimits {
+    pub fn get(&self, resource: &RateLimitResource) -> Option<&RateLimit> {
+        self.resources.get(resource)
+    }
+}
+
+#[async_trait]
+impl<'a> Action<(), RateLimits, GetRateLimitError> for GetRateLimit<'a> {
+    #[tracing::instrument]
+    async fn execute(&self, _input: ()) -> Result<RateLimits, GetRateLimitError> {
+        let rate_limits = self
+            .github_client
+            .get_with_options("/rate_limit", &self.request_options())
+            .await
+            .context("failed to query rate limit")?;
+
+        Ok(rate_limits)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GetRateLimitError {
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::mock;
+
+    use crate::action::Action;
+    use crate::github::client::RateLimitResource;
+    use crate::testing::client::github_client;
+    use crate::testing::token::mock_installation_access_tokens;
+
+    use super::GetRateLimit;
+
+    #[tokio::test]
+    async fn get_rate_limit_returns_resources() {
+        let _token_mock = mock_installation_access_tokens();
+        let _rate_limit_mock = mock("GET", "/rate_limit")
+            .with_status(200)
+            .with_body(
+                r#"
+                {
+                    "resources": {
+                        "core": {
+                            "limit": 5000,
+                            "used": 1,
+                            "remaining": 4999,
+                            "reset": 1691591363
+                        },
+                        "search": {
+                            "limit": 30,
+                            "used": 12,
+                            "remaining": 18,
+                            "reset": 1691591091
+                        }
+                    },
+                    "rate": {
+                        "limit": 5000,
+                        "used": 1,
+                        "remaining": 4999,
+                        "reset": 1372700873
+                    }
+                }
+            "#,
+            )
+            .create();
+
+        let github_client = github_client();
+
+        let rate_limits = GetRateLimit::new(&github_client).execute(()).await.unwrap();
+
+        let core = rate_limits.get(&RateLimitResource::new("core")).unwrap();
+        assert_eq!(4999, core.remaining());
+        assert_eq!(1691591363, core.reset().timestamp());
+        assert_eq!(2, rate_limits.resources().len());
+    }
+}
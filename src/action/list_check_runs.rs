@@ -1,13 +1,12 @@
 use anyhow::Context;
 use async_trait::async_trait;
 use derive_new::new;
-use reqwest::Method;
 
 use crate::account::Login;
 use crate::action::Action;
 use crate::check_run::CheckRun;
 use crate::check_suite::CheckSuiteId;
-use crate::github::client::GitHubClient;
+use crate::github::client::{GitHubClient, PaginationRequest};
 use crate::repository::RepositoryName;
 
 #[derive(Debug, new)]
@@ -33,7 +32,9 @@ impl<'a> Action<CheckSuiteId, Vec<CheckRun>, ListCheckRunsError> for ListCheckRu
 
         let check_runs = self
             .github_client
-            .paginate(Method::GET, &url, "check_runs")
+            .paginate_with(
+                &PaginationRequest::new(url, "check_runs").options(self.request_options()),
+            )
             .await
             .context("failed to query check runs")?;
 
@@ -1,13 +1,12 @@
 use anyhow::Context;
 use async_trait::async_trait;
 use derive_new::new;
-use reqwest::Method;
 
 use crate::account::Login;
 use crate::action::Action;
 use crate::check_suite::CheckSuite;
 use crate::git::HeadSha;
-use crate::github::client::GitHubClient;
+use crate::github::client::{GitHubClient, PaginationRequest};
 use crate::repository::RepositoryName;
 
 #[derive(Debug, new)]
@@ -30,7 +29,9 @@ impl<'a> Action<HeadSha, Vec<CheckSuite>, ListCheckRunsError> for ListCheckSuite
 
         let check_suites = self
             .github_client
-            .paginate(Method::GET, &url, "check_suites")
+            .paginate_with(
+                &PaginationRequest::new(url, "check_suites").options(self.request_options()),
+            )
             .await
             .context("failed to query check suites")?;
 
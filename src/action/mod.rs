@@ -2,9 +2,12 @@ use async_trait::async_trait;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
+use crate::github::client::RequestOptions;
+
 pub mod get_file;
 
 pub mod create_check_run;
+pub mod get_rate_limit;
 pub mod list_check_runs;
 pub mod list_check_suites;
 pub mod update_check_run;
@@ -17,4 +20,9 @@ where
     Error: std::error::Error,
 {
     async fn execute(&self, input: Input) -> Result<Output, Error>;
+
+    /// Returns the options for the requests of the action, e.g. the media type it needs.
+    fn request_options(&self) -> RequestOptions {
+        RequestOptions::default()
+    }
 }
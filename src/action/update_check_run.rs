@@ -31,7 +31,7 @@ impl<'a> Action<UpdateCheckRunInput, CheckRun, UpdateCheckRunError> for UpdateCh
 
         let check_run = self
             .github_client
-            .patch(&url, Some(input))
+            .patch_with_options(&url, Some(input), &self.request_options())
             .await
             .context("failed to update check run")?;
 
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("{1}")]
@@ -6,6 +8,11 @@ pub enum Error {
     #[error(transparent)]
     ExternalResource(#[from] reqwest::Error),
 
+    /// An error that occurred in an operation whose result is shared by multiple callers, for
+    /// example a token refresh that several tasks were waiting for.
+    #[error(transparent)]
+    Shared(#[from] Arc<Error>),
+
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
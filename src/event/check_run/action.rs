@@ -8,6 +8,9 @@ pub enum Action {
     Created,
     Completed,
     Rerequested,
+    // Mirrors the `requested_action` value in GitHub's payload, which newer versions of clippy
+    // flag for ending with the name of the enum.
+    #[allow(clippy::enum_variant_names)]
     RequestedAction,
 }
 
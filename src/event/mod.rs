@@ -9,17 +9,17 @@ mod check_run;
 #[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
 pub enum Event {
     CheckRun(Box<CheckRunEvent>),
-    Unsupported(serde_json::Value),
+    /// An event that is not modelled yet, with its name from the `X-GitHub-Event` header and
+    /// its raw payload.
+    Unsupported(String, serde_json::Value),
 }
 
 impl Display for Event {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let string = match self {
-            Event::CheckRun(_) => "check run",
-            Event::Unsupported(_) => "unsupported event",
-        };
-
-        write!(f, "{}", string)
+        match self {
+            Event::CheckRun(_) => write!(f, "check run"),
+            Event::Unsupported(name, _) => write!(f, "unsupported event {}", name),
+        }
     }
 }
 
@@ -31,9 +31,9 @@ mod tests {
 
     #[test]
     fn trait_display() {
-        let event = Event::Unsupported(json!({}));
+        let event = Event::Unsupported("ping".into(), json!({}));
 
-        assert_eq!("unsupported event", event.to_string());
+        assert_eq!("unsupported event ping", event.to_string());
     }
 
     #[test]
@@ -0,0 +1,309 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use futures::lock::Mutex;
+use reqwest::Client;
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::github::auth::{bearer, fingerprint, Authenticator};
+use crate::github::http_client::http_client_or_default;
+use crate::github::GitHubHost;
+
+const DEFAULT_REFRESH_SKEW_SECONDS: i64 = 60;
+
+/// The tokens of a user that has authorized a GitHub App or an OAuth App.
+#[derive(Clone, Debug)]
+pub struct UserCredentials {
+    access_token: SecretString,
+    expires_at: Option<DateTime<Utc>>,
+    refresh_token: Option<SecretString>,
+    refresh_token_expires_at: Option<DateTime<Utc>>,
+}
+
+impl UserCredentials {
+    /// Creates credentials from an access token and, if the token expires, the refresh token
+    /// that can be exchanged for a new one.
+    pub fn new(
+        access_token: String,
+        expires_at: Option<DateTime<Utc>>,
+        refresh_token: Option<String>,
+    ) -> Self {
+        Self {
+            access_token: SecretString::new(access_token),
+            expires_at,
+            refresh_token: refresh_token.map(SecretString::new),
+            refresh_token_expires_at: None,
+        }
+    }
+
+    pub fn access_token(&self) -> &str {
+        self.access_token.expose_secret()
+    }
+
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expires_at
+    }
+
+    pub fn refresh_token(&self) -> Option<&str> {
+        self.refresh_token
+            .as_ref()
+            .map(|refresh_token| refresh_token.expose_secret().as_str())
+    }
+
+    pub fn refresh_token_expires_at(&self) -> Option<DateTime<Utc>> {
+        self.refresh_token_expires_at
+    }
+}
+
+/// Authenticates as a user with a user-to-server token, and refreshes the token when it expires.
+///
+/// GitHub rotates the refresh token every time it is used, and the old refresh token becomes
+/// invalid. Callers that persist the credentials must read them back with
+/// [`UserAuthenticator::credentials`] after a refresh.
+#[derive(Clone, Debug)]
+pub struct UserAuthenticator {
+    github_host: GitHubHost,
+    client_id: String,
+    client_secret: SecretString,
+    refresh_skew: Duration,
+    credentials: Arc<Mutex<UserCredentials>>,
+    http_client: Option<Client>,
+}
+
+impl UserAuthenticator {
+    /// Creates an authenticator for the user.
+    ///
+    /// Access tokens are refreshed through the web interface of the host, not its API.
+    pub fn new(
+        github_host: GitHubHost,
+        client_id: String,
+        client_secret: String,
+        credentials: UserCredentials,
+    ) -> Self {
+        Self {
+            github_host,
+            client_id,
+            client_secret: SecretString::new(client_secret),
+            refresh_skew: Duration::seconds(DEFAULT_REFRESH_SKEW_SECONDS),
+            credentials: Arc::new(Mutex::new(credentials)),
+            http_client: None,
+        }
+    }
+
+    /// Refreshes the access token this long before it actually expires.
+    pub fn with_refresh_skew(mut self, refresh_skew: Duration) -> Self {
+        self.refresh_skew = refresh_skew;
+        self
+    }
+
+    /// Sends the requests that refresh the access token with the given HTTP client.
+    pub fn with_http_client(mut self, http_client: Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    pub async fn credentials(&self) -> UserCredentials {
+        self.credentials.lock().await.clone()
+    }
+
+    #[tracing::instrument]
+    async fn refresh(&self, refresh_token: &SecretString) -> Result<UserCredentials, Error> {
+        let url = self.github_host.web_url("/login/oauth/access_token");
+        let now = Utc::now();
+
+        let response = http_client_or_default(self.http_client.as_ref())?
+            .post(url)
+            .header("Accept", "application/json")
+            .form(&[
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.expose_secret()),
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token.expose_secret()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?;
+
+        // GitHub reports errors such as an expired refresh token with a successful status code.
+        match response.json::<RefreshResponse>().await? {
+            RefreshResponse::Success {
+                access_token,
+                expires_in,
+                refresh_token,
+                refresh_token_expires_in,
+            } => Ok(UserCredentials {
+                access_token: SecretString::new(access_token),
+                expires_at: expires_in.map(|seconds| now + Duration::seconds(seconds)),
+                refresh_token: refresh_token.map(SecretString::new),
+                refresh_token_expires_at: refresh_token_expires_in
+                    .map(|seconds| now + Duration::seconds(seconds)),
+            }),
+            RefreshResponse::Error {
+                error,
+                error_description,
+            } => Err(anyhow!(
+                "{}",
+                error_description.unwrap_or_else(|| error.clone())
+            ))
+            .context(format!("failed to refresh user access token: {}", error))?,
+        }
+    }
+}
+
+#[async_trait]
+impl Authenticator for UserAuthenticator {
+    #[tracing::instrument]
+    async fn authorization(&self) -> Result<SecretString, Error> {
+        // Holding the lock while refreshing ensures that the refresh token, which can only be
+        // used once, is not sent twice by concurrent requests.
+        let mut credentials = self.credentials.lock().await;
+
+        let must_refresh = credentials
+            .expires_at
+            .map(|expires_at| expires_at <= Utc::now() + self.refresh_skew)
+            .unwrap_or(false);
+
+        if must_refresh {
+            let refresh_token = credentials
+                .refresh_token
+                .clone()
+                .context("user access token has expired and cannot be refreshed")?;
+
+            *credentials = self.refresh(&refresh_token).await?;
+        }
+
+        Ok(bearer(credentials.access_token.expose_secret()))
+    }
+
+    async fn identity(&self) -> Option<String> {
+        // The user is only known by their token, which changes with every refresh.
+        let credentials = self.credentials.lock().await;
+
+        Some(format!(
+            "user:{}",
+            fingerprint(credentials.access_token.expose_secret())
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RefreshResponse {
+    Success {
+        access_token: String,
+        expires_in: Option<i64>,
+        refresh_token: Option<String>,
+        refresh_token_expires_in: Option<i64>,
+    },
+    Error {
+        error: String,
+        error_description: Option<String>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::{Add, Sub};
+
+    use chrono::{Duration, Utc};
+    use mockito::{mock, Matcher};
+    use secrecy::ExposeSecret;
+
+    use crate::github::auth::Authenticator;
+    use crate::github::GitHubHost;
+
+    use super::{UserAuthenticator, UserCredentials};
+
+    fn authenticator(expires_at: chrono::DateTime<Utc>) -> UserAuthenticator {
+        UserAuthenticator::new(
+            GitHubHost::new(&mockito::server_url()).unwrap(),
+            "Iv1.8a61f9b3a7aba766".into(),
+            "secret".into(),
+            UserCredentials::new("ghu_old".into(), Some(expires_at), Some("ghr_old".into())),
+        )
+    }
+
+    #[tokio::test]
+    async fn authorization_uses_valid_access_token() {
+        let authenticator = authenticator(Utc::now().add(Duration::hours(8)));
+
+        let authorization = authenticator.authorization().await.unwrap();
+
+        assert_eq!("Bearer ghu_old", authorization.expose_secret());
+    }
+
+    #[tokio::test]
+    async fn authorization_refreshes_expired_access_token() {
+        let mock = mock("POST", "/login/oauth/access_token")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("grant_type".into(), "refresh_token".into()),
+                Matcher::UrlEncoded("refresh_token".into(), "ghr_old".into()),
+                Matcher::UrlEncoded("client_id".into(), "Iv1.8a61f9b3a7aba766".into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"
+                {
+                    "access_token": "ghu_new",
+                    "expires_in": 28800,
+                    "refresh_token": "ghr_new",
+                    "refresh_token_expires_in": 15811200,
+                    "scope": "",
+                    "token_type": "bearer"
+                }
+            "#,
+            )
+            .expect(1)
+            .create();
+
+        let authenticator = authenticator(Utc::now().sub(Duration::minutes(1)));
+
+        let first = authenticator.authorization().await.unwrap();
+        let second = authenticator.authorization().await.unwrap();
+        let credentials = authenticator.credentials().await;
+
+        mock.assert();
+        assert_eq!("Bearer ghu_new", first.expose_secret());
+        assert_eq!("Bearer ghu_new", second.expose_secret());
+        assert_eq!(Some("ghr_new"), credentials.refresh_token());
+        assert!(credentials.expires_at().unwrap() > Utc::now().add(Duration::hours(7)));
+    }
+
+    #[tokio::test]
+    async fn authorization_fails_when_refresh_is_rejected() {
+        let _mock = mock("POST", "/login/oauth/access_token")
+            .with_status(200)
+            .with_body(
+                r#"
+                {
+                    "error": "bad_refresh_token",
+                    "error_description": "The refresh token passed is incorrect or expired.",
+                    "error_uri": "https://docs.github.com"
+                }
+            "#,
+            )
+            .create();
+
+        let authenticator = authenticator(Utc::now().sub(Duration::minutes(1)));
+
+        let error = authenticator.authorization().await.unwrap_err();
+
+        assert!(format!("{:?}", error).contains("bad_refresh_token"));
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<UserAuthenticator>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<UserAuthenticator>();
+    }
+}
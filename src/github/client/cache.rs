@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use getset::Getters;
+use parking_lot::Mutex;
+use reqwest::header::{HeaderMap, HeaderValue, ETAG, LAST_MODIFIED, LINK};
+
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Stores the responses of `GET` requests so that the client can make conditional requests.
+///
+/// GitHub does not count a `304 Not Modified` response against the rate limit, which makes
+/// polling an endpoint for changes much cheaper. A cache can be shared by many clients.
+///
+/// Responses are keyed by the identity of the authenticator, e.g. the installation, and the URL,
+/// media type and API version of the request.
+#[async_trait]
+pub trait ResponseCache: Debug + Send + Sync {
+    async fn get(&self, key: &str) -> Option<CachedResponse>;
+
+    async fn insert(&self, key: &str, response: CachedResponse);
+}
+
+/// A response that can be validated with `If-None-Match` or `If-Modified-Since`.
+#[derive(Clone, Eq, PartialEq, Debug, Getters)]
+pub struct CachedResponse {
+    #[getset(get = "pub")]
+    etag: Option<String>,
+
+    #[getset(get = "pub")]
+    last_modified: Option<String>,
+
+    /// The `link` header, which is required to paginate cached responses.
+    #[getset(get = "pub")]
+    link: Option<String>,
+
+    #[getset(get = "pub")]
+    body: Vec<u8>,
+}
+
+impl CachedResponse {
+    pub fn new(
+        etag: Option<String>,
+        last_modified: Option<String>,
+        link: Option<String>,
+        body: Vec<u8>,
+    ) -> Self {
+        Self {
+            etag,
+            last_modified,
+            link,
+            body,
+        }
+    }
+
+    /// Creates a cached response if the response has a validator, and `None` otherwise.
+    pub(super) fn from_parts(headers: &HeaderMap, body: &[u8]) -> Option<Self> {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|header: &HeaderValue| header.to_str().ok())
+                .map(String::from)
+        };
+
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+
+        if etag.is_none() && last_modified.is_none() {
+            return None;
+        }
+
+        Some(Self::new(etag, last_modified, header(LINK), body.to_vec()))
+    }
+
+    /// Returns the headers of the original response that the client needs.
+    pub(super) fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        let values = [
+            (ETAG, &self.etag),
+            (LAST_MODIFIED, &self.last_modified),
+            (LINK, &self.link),
+        ];
+
+        for (name, value) in values {
+            if let Some(value) = value.as_ref().and_then(|value| value.parse().ok()) {
+                headers.insert(name, value);
+            }
+        }
+
+        headers
+    }
+}
+
+/// Keeps the most recently used responses in memory.
+#[derive(Debug)]
+pub struct InMemoryResponseCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    clock: u64,
+    responses: HashMap<String, (u64, CachedResponse)>,
+}
+
+impl InMemoryResponseCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().responses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for InMemoryResponseCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[async_trait]
+impl ResponseCache for InMemoryResponseCache {
+    async fn get(&self, key: &str) -> Option<CachedResponse> {
+        let mut entries = self.entries.lock();
+
+        entries.clock += 1;
+        let clock = entries.clock;
+
+        entries.responses.get_mut(key).map(|(last_used, response)| {
+            *last_used = clock;
+            response.clone()
+        })
+    }
+
+    async fn insert(&self, key: &str, response: CachedResponse) {
+        let mut entries = self.entries.lock();
+
+        entries.clock += 1;
+        let clock = entries.clock;
+
+        if !entries.responses.contains_key(key) && entries.responses.len() >= self.capacity {
+            let least_recently_used = entries
+                .responses
+                .iter()
+                .min_by_key(|(_, (last_used, _))| *last_used)
+                .map(|(key, _)| key.clone());
+
+            if let Some(key) = least_recently_used {
+                entries.responses.remove(&key);
+            }
+        }
+
+        if self.capacity > 0 {
+            entries.responses.insert(key.into(), (clock, response));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderMap, HeaderValue, ETAG, LINK};
+
+    use super::{CachedResponse, InMemoryResponseCache, ResponseCache};
+
+    fn response(etag: &str) -> CachedResponse {
+        CachedResponse::new(Some(etag.into()), None, None, b"{}".to_vec())
+    }
+
+    #[tokio::test]
+    async fn get_returns_inserted_response() {
+        let cache = InMemoryResponseCache::default();
+
+        cache
+            .insert("/repos/octocat/Hello-World", response("\"a\""))
+            .await;
+
+        let cached = cache.get("/repos/octocat/Hello-World").await.unwrap();
+        assert_eq!(Some("\"a\""), cached.etag().as_deref());
+    }
+
+    #[tokio::test]
+    async fn insert_evicts_least_recently_used_response() {
+        let cache = InMemoryResponseCache::new(2);
+
+        cache.insert("/a", response("\"a\"")).await;
+        cache.insert("/b", response("\"b\"")).await;
+        cache.get("/a").await;
+        cache.insert("/c", response("\"c\"")).await;
+
+        assert_eq!(2, cache.len());
+        assert!(cache.get("/a").await.is_some());
+        assert!(cache.get("/b").await.is_none());
+        assert!(cache.get("/c").await.is_some());
+    }
+
+    #[test]
+    fn from_parts_requires_validator() {
+        let mut headers = HeaderMap::new();
+
+        assert!(CachedResponse::from_parts(&headers, b"{}").is_none());
+
+        headers.insert(ETAG, HeaderValue::from_static("\"a\""));
+        headers.insert(
+            LINK,
+            HeaderValue::from_static("<https://next>; rel=\"next\""),
+        );
+
+        let cached = CachedResponse::from_parts(&headers, b"{}").unwrap();
+        assert_eq!(headers, cached.headers());
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<InMemoryResponseCache>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<InMemoryResponseCache>();
+    }
+}
@@ -0,0 +1,319 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use getset::{CopyGetters, Getters};
+use reqwest::{Response, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use crate::github::client::GraphQlErrors;
+
+#[derive(Debug, thiserror::Error)]
+pub enum GitHubClientError {
+    #[error("failed to authenticate with GitHub: {0}")]
+    Unauthorized(GitHubErrorResponse),
+
+    #[error("access to the requested resource was denied: {0}")]
+    Forbidden(GitHubErrorResponse),
+
+    #[error("failed to find the requested resource: {0}")]
+    NotFound(GitHubErrorResponse),
+
+    #[error("request conflicts with the current state of the resource: {0}")]
+    Conflict(GitHubErrorResponse),
+
+    #[error("requested resource is no longer available: {0}")]
+    Gone(GitHubErrorResponse),
+
+    #[error("validation failed: {0}")]
+    UnprocessableEntity(GitHubErrorResponse),
+
+    #[error("too many requests: {0}")]
+    TooManyRequests(GitHubErrorResponse),
+
+    #[error("GitHub failed to process the request: {0}")]
+    ServerError(GitHubErrorResponse),
+
+    #[error("unexpected response from GitHub: {0}")]
+    UnexpectedStatus(GitHubErrorResponse),
+
+    #[error("rate limit is exhausted until {0}")]
+    RateLimited(DateTime<Utc>),
+
+    #[error("secondary rate limit was exceeded: {0}")]
+    SecondaryRateLimited(GitHubErrorResponse),
+
+    #[error("GraphQL query failed: {0}")]
+    GraphQl(GraphQlErrors),
+
+    #[error("{0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error(transparent)]
+    UnexpectedError(#[from] anyhow::Error),
+}
+
+impl GitHubClientError {
+    /// Turns an unsuccessful response into the error that matches its status code.
+    pub(crate) async fn from_response(response: Response) -> Self {
+        let response = GitHubErrorResponse::from_response(response).await;
+
+        Self::from_error_response(response)
+    }
+
+    pub(crate) fn from_error_response(response: GitHubErrorResponse) -> Self {
+        match response.status.as_u16() {
+            401 => GitHubClientError::Unauthorized(response),
+            403 => GitHubClientError::Forbidden(response),
+            404 => GitHubClientError::NotFound(response),
+            409 => GitHubClientError::Conflict(response),
+            410 => GitHubClientError::Gone(response),
+            422 => GitHubClientError::UnprocessableEntity(response),
+            429 => GitHubClientError::TooManyRequests(response),
+            500..=599 => GitHubClientError::ServerError(response),
+            _ => GitHubClientError::UnexpectedStatus(response),
+        }
+    }
+
+    /// Returns the response that GitHub sent, if the request failed with an error status.
+    pub fn response(&self) -> Option<&GitHubErrorResponse> {
+        match self {
+            GitHubClientError::Unauthorized(response)
+            | GitHubClientError::Forbidden(response)
+            | GitHubClientError::NotFound(response)
+            | GitHubClientError::Conflict(response)
+            | GitHubClientError::Gone(response)
+            | GitHubClientError::UnprocessableEntity(response)
+            | GitHubClientError::TooManyRequests(response)
+            | GitHubClientError::ServerError(response)
+            | GitHubClientError::UnexpectedStatus(response)
+            | GitHubClientError::SecondaryRateLimited(response) => Some(response),
+            GitHubClientError::RateLimited(_)
+            | GitHubClientError::GraphQl(_)
+            | GitHubClientError::Request(_)
+            | GitHubClientError::UnexpectedError(_) => None,
+        }
+    }
+}
+
+/// An unsuccessful response from GitHub.
+#[derive(Clone, Eq, PartialEq, Debug, CopyGetters, Getters)]
+pub struct GitHubErrorResponse {
+    #[getset(get_copy = "pub")]
+    status: StatusCode,
+
+    /// The value of the `x-github-request-id` header, which GitHub Support asks for when
+    /// investigating a failed request.
+    #[getset(get = "pub")]
+    request_id: Option<String>,
+
+    /// The value of the `retry-after` header, which GitHub sends when a secondary rate limit
+    /// was exceeded.
+    #[getset(get_copy = "pub")]
+    retry_after: Option<Duration>,
+
+    #[getset(get = "pub")]
+    body: GitHubErrorBody,
+}
+
+impl GitHubErrorResponse {
+    pub(crate) async fn from_response(response: Response) -> Self {
+        let status = response.status();
+        let request_id = response
+            .headers()
+            .get("x-github-request-id")
+            .and_then(|header| header.to_str().ok())
+            .map(String::from);
+        let retry_after = response
+            .headers()
+            .get("retry-after")
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| header.parse().ok())
+            .map(Duration::from_secs);
+
+        let text = response.text().await.unwrap_or_default();
+
+        // Not every error has a JSON body, for example errors from proxies in front of GitHub.
+        let body = serde_json::from_str(&text).unwrap_or_else(|_| GitHubErrorBody {
+            message: if text.is_empty() {
+                status
+                    .canonical_reason()
+                    .unwrap_or_else(|| status.as_str())
+                    .into()
+            } else {
+                text
+            },
+            documentation_url: None,
+            errors: Vec::new(),
+        });
+
+        Self {
+            status,
+            request_id,
+            retry_after,
+            body,
+        }
+    }
+
+    /// Checks if the request was rejected by one of GitHub's secondary rate limits, which
+    /// protect against abuse, e.g. too many concurrent or mutating requests.
+    pub fn is_secondary_rate_limit(&self) -> bool {
+        if self.status != StatusCode::FORBIDDEN && self.status != StatusCode::TOO_MANY_REQUESTS {
+            return false;
+        }
+
+        let message = self.body.message.to_lowercase();
+
+        self.retry_after.is_some()
+            || message.contains("secondary rate limit")
+            || message.contains("abuse detection")
+    }
+}
+
+impl Display for GitHubErrorResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.status.as_u16(), self.body.message)?;
+
+        if let Some(request_id) = &self.request_id {
+            write!(f, " (request id {})", request_id)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The error document that GitHub returns in the body of unsuccessful responses.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize, Getters)]
+pub struct GitHubErrorBody {
+    #[getset(get = "pub")]
+    message: String,
+
+    #[getset(get = "pub")]
+    documentation_url: Option<String>,
+
+    #[getset(get = "pub")]
+    #[serde(default)]
+    errors: Vec<GitHubErrorDetail>,
+}
+
+impl GitHubErrorBody {
+    /// Checks if any of the errors has the given code, e.g. `already_exists`.
+    pub fn has_error_code(&self, code: &str) -> bool {
+        self.errors
+            .iter()
+            .any(|error| error.code().as_deref() == Some(code))
+    }
+}
+
+/// A single error in a GitHub error document, usually a failed validation.
+///
+/// GitHub sometimes returns plain strings instead of objects, which are stored as the message.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize, Getters)]
+#[serde(from = "RawErrorDetail")]
+pub struct GitHubErrorDetail {
+    #[getset(get = "pub")]
+    resource: Option<String>,
+
+    #[getset(get = "pub")]
+    field: Option<String>,
+
+    #[getset(get = "pub")]
+    code: Option<String>,
+
+    #[getset(get = "pub")]
+    message: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawErrorDetail {
+    Message(String),
+    Detail {
+        resource: Option<String>,
+        field: Option<String>,
+        code: Option<String>,
+        message: Option<String>,
+    },
+}
+
+impl From<RawErrorDetail> for GitHubErrorDetail {
+    fn from(raw: RawErrorDetail) -> Self {
+        match raw {
+            RawErrorDetail::Message(message) => Self {
+                resource: None,
+                field: None,
+                code: None,
+                message: Some(message),
+            },
+            RawErrorDetail::Detail {
+                resource,
+                field,
+                code,
+                message,
+            } => Self {
+                resource,
+                field,
+                code,
+                message,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GitHubClientError, GitHubErrorBody};
+
+    #[test]
+    fn error_body_deserializes_validation_errors() {
+        let json = r#"
+        {
+            "message": "Validation Failed",
+            "errors": [
+                {
+                    "resource": "Label",
+                    "code": "already_exists",
+                    "field": "name"
+                }
+            ],
+            "documentation_url": "https://docs.github.com/rest/issues/labels#create-a-label"
+        }
+        "#;
+
+        let body: GitHubErrorBody = serde_json::from_str(json).unwrap();
+
+        assert_eq!("Validation Failed", body.message());
+        assert_eq!(Some("name"), body.errors()[0].field().as_deref());
+        assert!(body.has_error_code("already_exists"));
+    }
+
+    #[test]
+    fn error_body_deserializes_string_errors() {
+        let json = r#"
+        {
+            "message": "Validation Failed",
+            "errors": ["Only one ref can be updated at a time"]
+        }
+        "#;
+
+        let body: GitHubErrorBody = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            Some("Only one ref can be updated at a time"),
+            body.errors()[0].message().as_deref()
+        );
+        assert!(body.documentation_url().is_none());
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<GitHubClientError>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<GitHubClientError>();
+    }
+}
@@ -0,0 +1,320 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::{anyhow, Context};
+use getset::Getters;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::github::client::GitHubClientError;
+
+const BLOCK_QUOTE: &str = "\"\"\"";
+
+/// An error in the `errors` field of a GraphQL response.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize, Getters)]
+pub struct GraphQlError {
+    #[getset(get = "pub")]
+    message: String,
+
+    /// The type of the error, e.g. `NOT_FOUND` or `FORBIDDEN`.
+    #[getset(get = "pub")]
+    #[serde(rename = "type")]
+    error_type: Option<String>,
+
+    /// The path of the field in the query that caused the error.
+    #[getset(get = "pub")]
+    #[serde(default)]
+    path: Vec<Value>,
+
+    #[getset(get = "pub")]
+    #[serde(default)]
+    locations: Vec<GraphQlLocation>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct GraphQlLocation {
+    pub line: u64,
+    pub column: u64,
+}
+
+/// The errors of a GraphQL response, which GitHub reports with a successful status code.
+#[derive(Clone, Eq, PartialEq, Debug, Deserialize, Serialize)]
+pub struct GraphQlErrors(Vec<GraphQlError>);
+
+impl GraphQlErrors {
+    pub fn get(&self) -> &[GraphQlError] {
+        &self.0
+    }
+
+    /// Checks if any of the errors has the given type, e.g. `NOT_FOUND`.
+    pub fn has_error_type(&self, error_type: &str) -> bool {
+        self.0
+            .iter()
+            .any(|error| error.error_type.as_deref() == Some(error_type))
+    }
+}
+
+impl Display for GraphQlErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let messages: Vec<&str> = self.0.iter().map(|error| error.message.as_str()).collect();
+
+        write!(f, "{}", messages.join(", "))
+    }
+}
+
+#[derive(Serialize)]
+pub(super) struct GraphQlRequest<'a, V> {
+    pub query: &'a str,
+    pub variables: V,
+}
+
+#[derive(Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+/// Reads the data of a GraphQL response, and fails if the response has any errors.
+///
+/// GitHub returns partial data alongside errors, e.g. when one of several nodes is not
+/// accessible. Partial data is discarded, since callers cannot tell which fields are missing.
+pub(super) fn data<T>(body: &[u8]) -> Result<T, GitHubClientError>
+where
+    T: DeserializeOwned,
+{
+    let response: GraphQlResponse<T> =
+        serde_json::from_slice(body).context("failed to deserialize GraphQL response")?;
+
+    if !response.errors.is_empty() {
+        return Err(GitHubClientError::GraphQl(GraphQlErrors(response.errors)));
+    }
+
+    response
+        .data
+        .ok_or_else(|| anyhow!("GraphQL response has neither data nor errors").into())
+}
+
+/// Returns the nodes of the connection at the dot-separated path, e.g.
+/// `repository.pullRequests`, and the cursor of the next page if there is one.
+pub(super) fn connection<T>(
+    data: &mut Value,
+    path: &str,
+) -> Result<(Vec<T>, Option<String>), GitHubClientError>
+where
+    T: DeserializeOwned,
+{
+    let connection = path
+        .split('.')
+        .try_fold(data, |value, field| value.get_mut(field))
+        .with_context(|| format!("failed to find connection {} in GraphQL response", path))?;
+
+    let nodes = serde_json::from_value(connection["nodes"].take())
+        .context("failed to deserialize nodes of GraphQL connection")?;
+    let page_info: PageInfo = serde_json::from_value(connection["pageInfo"].take())
+        .context("failed to find pageInfo of GraphQL connection")?;
+
+    let cursor = match page_info {
+        PageInfo {
+            has_next_page: true,
+            end_cursor: Some(cursor),
+        } => Some(cursor),
+        _ => None,
+    };
+
+    Ok((nodes, cursor))
+}
+
+/// Checks if the document defines a mutation, which is the only kind of operation that changes
+/// data. Fragments and the selections of other operations are skipped, as are comments and strings.
+pub(super) fn is_mutation(document: &str) -> bool {
+    let mut depth = 0usize;
+    let mut chars = document.char_indices().peekable();
+
+    while let Some((start, char)) = chars.next() {
+        match char {
+            '#' => {
+                for (_, char) in chars.by_ref() {
+                    if char == '\n' {
+                        break;
+                    }
+                }
+            }
+            '"' => {
+                let block = document[start..].starts_with(BLOCK_QUOTE);
+                let end = if block {
+                    document[start + 3..]
+                        .find(BLOCK_QUOTE)
+                        .map(|end| start + 3 + end + 3)
+                } else {
+                    skip_string(&document[start + 1..]).map(|end| start + 1 + end)
+                };
+
+                let end = end.unwrap_or(document.len());
+                while chars.next_if(|(index, _)| *index < end).is_some() {}
+            }
+            '{' | '(' => depth += 1,
+            '}' | ')' => depth = depth.saturating_sub(1),
+            char if depth == 0 && (char.is_ascii_alphabetic() || char == '_') => {
+                let mut end = start + char.len_utf8();
+                while let Some((index, _)) =
+                    chars.next_if(|(_, char)| char.is_ascii_alphanumeric() || *char == '_')
+                {
+                    end = index + 1;
+                }
+
+                if &document[start..end] == "mutation" {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    false
+}
+
+/// Returns the length of a string up to and including its closing quote.
+fn skip_string(string: &str) -> Option<usize> {
+    let mut escaped = false;
+
+    for (index, char) in string.char_indices() {
+        match char {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(index + 1),
+            _ => escaped = false,
+        }
+    }
+
+    None
+}
+
+/// Returns the variables of a paginated query with the cursor of the next page.
+pub(super) fn with_cursor(
+    variables: &Map<String, Value>,
+    cursor: Option<&str>,
+) -> Map<String, Value> {
+    let mut variables = variables.clone();
+    variables.insert("cursor".into(), cursor.into());
+
+    variables
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map, Value};
+
+    use crate::github::client::GitHubClientError;
+
+    use super::{connection, data, is_mutation, with_cursor, GraphQlErrors};
+
+    #[test]
+    fn data_fails_with_errors() {
+        let body = br#"{
+            "data": { "repository": null },
+            "errors": [
+                {
+                    "type": "NOT_FOUND",
+                    "path": ["repository"],
+                    "locations": [{ "line": 1, "column": 3 }],
+                    "message": "Could not resolve to a Repository with the name 'octocat/missing'."
+                }
+            ]
+        }"#;
+
+        let error = data::<Value>(body).unwrap_err();
+
+        match error {
+            GitHubClientError::GraphQl(errors) => {
+                assert!(errors.has_error_type("NOT_FOUND"));
+                assert_eq!(
+                    "Could not resolve to a Repository with the name 'octocat/missing'.",
+                    errors.to_string()
+                );
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+    }
+
+    #[test]
+    fn connection_returns_nodes_and_cursor() {
+        let mut data = json!({
+            "repository": {
+                "pullRequests": {
+                    "nodes": [{ "number": 1 }, { "number": 2 }],
+                    "pageInfo": { "hasNextPage": true, "endCursor": "Y3Vyc29yOjI=" }
+                }
+            }
+        });
+
+        let (nodes, cursor): (Vec<Value>, _) =
+            connection(&mut data, "repository.pullRequests").unwrap();
+
+        assert_eq!(2, nodes.len());
+        assert_eq!(Some("Y3Vyc29yOjI=".into()), cursor);
+    }
+
+    #[test]
+    fn connection_ends_on_last_page() {
+        let mut data = json!({
+            "viewer": {
+                "repositories": {
+                    "nodes": [],
+                    "pageInfo": { "hasNextPage": false, "endCursor": "Y3Vyc29yOjI=" }
+                }
+            }
+        });
+
+        let (_, cursor): (Vec<Value>, _) = connection(&mut data, "viewer.repositories").unwrap();
+
+        assert_eq!(None, cursor);
+    }
+
+    #[test]
+    fn is_mutation_detects_operation_type() {
+        assert!(is_mutation(
+            "mutation($id: ID!) { addStar(input: { starrableId: $id }) { clientMutationId } }"
+        ));
+        assert!(is_mutation(
+            "# Stars a repository\nfragment Id on Node { id }\nmutation Star { addStar(input: {}) { starrable { ...Id } } }"
+        ));
+
+        assert!(!is_mutation("{ viewer { login } }"));
+        assert!(!is_mutation(
+            "query Mutation { repository(name: \"mutation\") { mutation: id } } # mutation"
+        ));
+    }
+
+    #[test]
+    fn with_cursor_sets_cursor_variable() {
+        let mut variables = Map::new();
+        variables.insert("owner".into(), "octocat".into());
+
+        let variables = with_cursor(&variables, Some("Y3Vyc29yOjI="));
+
+        assert_eq!(
+            json!({ "owner": "octocat", "cursor": "Y3Vyc29yOjI=" }),
+            Value::Object(variables)
+        );
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<GraphQlErrors>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<GraphQlErrors>();
+    }
+}
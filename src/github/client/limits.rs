@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+
+use futures::lock::Mutex;
+
+use crate::github::client::rate_limit::{RateLimit, RateLimitResource};
+
+type RateLimits = parking_lot::Mutex<HashMap<RateLimitResource, RateLimit>>;
+
+/// The limits that every client of an installation shares.
+///
+/// GitHub counts requests per installation, not per client, so clients that are built
+/// independently for the same installation must draw from the same budget.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SharedLimits {
+    pub(super) rate_limits: Arc<RateLimits>,
+
+    /// Serializes the mutating requests of the installation.
+    pub(super) mutations: Arc<Mutex<()>>,
+}
+
+impl SharedLimits {
+    pub(crate) fn downgrade(&self) -> WeakLimits {
+        WeakLimits {
+            rate_limits: Arc::downgrade(&self.rate_limits),
+            mutations: Arc::downgrade(&self.mutations),
+        }
+    }
+}
+
+/// A reference to the limits of an installation that does not keep them alive.
+///
+/// The limits are released when the last client of the installation is dropped, so that a
+/// long-running process does not keep the state of every installation it has ever seen.
+#[derive(Clone, Debug)]
+pub(crate) struct WeakLimits {
+    rate_limits: Weak<RateLimits>,
+    mutations: Weak<Mutex<()>>,
+}
+
+impl WeakLimits {
+    pub(crate) fn upgrade(&self) -> Option<SharedLimits> {
+        Some(SharedLimits {
+            rate_limits: self.rate_limits.upgrade()?,
+            mutations: self.mutations.upgrade()?,
+        })
+    }
+
+    pub(crate) fn is_released(&self) -> bool {
+        self.rate_limits.strong_count() == 0 || self.mutations.strong_count() == 0
+    }
+}
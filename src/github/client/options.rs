@@ -0,0 +1,194 @@
+use std::fmt::{Display, Formatter};
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use crate::name;
+
+const DEFAULT_API_VERSION: &str = "2022-11-28";
+
+name!(ApiVersion);
+
+impl Default for ApiVersion {
+    fn default() -> Self {
+        Self::new(DEFAULT_API_VERSION)
+    }
+}
+
+/// The representation of a resource that the client asks GitHub for.
+///
+/// `Raw`, `Text`, `Html` and `Full` select how bodies of e.g. issues, comments or files are
+/// rendered. `Diff`, `Patch` and `Sha` return a commit or pull request in another format, and
+/// their responses are not JSON. `Object` returns the contents of a directory as an object with
+/// its `entries`, instead of a bare array.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub enum MediaType {
+    #[default]
+    Json,
+    Raw,
+    Text,
+    Html,
+    Full,
+    Diff,
+    Patch,
+    Sha,
+    Object,
+    /// Any other media type, e.g. a preview of the API.
+    Custom(String),
+}
+
+impl MediaType {
+    pub fn accept(&self) -> &str {
+        match self {
+            MediaType::Json => "application/vnd.github+json",
+            MediaType::Raw => "application/vnd.github.raw+json",
+            MediaType::Text => "application/vnd.github.text+json",
+            MediaType::Html => "application/vnd.github.html+json",
+            MediaType::Full => "application/vnd.github.full+json",
+            MediaType::Diff => "application/vnd.github.diff",
+            MediaType::Patch => "application/vnd.github.patch",
+            MediaType::Sha => "application/vnd.github.sha",
+            MediaType::Object => "application/vnd.github.object+json",
+            MediaType::Custom(media_type) => media_type,
+        }
+    }
+}
+
+impl Display for MediaType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.accept())
+    }
+}
+
+/// Options that change how the client sends a single request.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct RequestOptions {
+    retry_non_idempotent: bool,
+    api_version: Option<ApiVersion>,
+    media_type: MediaType,
+    read_only: bool,
+}
+
+impl RequestOptions {
+    /// Allows retrying a `POST` or `PATCH` request after a transient failure.
+    ///
+    /// These methods are not idempotent, so a retry can create a resource twice if the first
+    /// request reached GitHub. Only opt in if the request can safely be repeated.
+    pub fn retry_non_idempotent(mut self, retry_non_idempotent: bool) -> Self {
+        self.retry_non_idempotent = retry_non_idempotent;
+        self
+    }
+
+    /// Overrides the API version of the client for this request.
+    pub fn api_version(mut self, api_version: ApiVersion) -> Self {
+        self.api_version = Some(api_version);
+        self
+    }
+
+    pub fn media_type(mut self, media_type: MediaType) -> Self {
+        self.media_type = media_type;
+        self
+    }
+
+    /// Checks if the request may be retried after a transient failure.
+    pub fn is_retryable(&self, method: &Method) -> bool {
+        if self.read_only {
+            return true;
+        }
+
+        match *method {
+            Method::POST | Method::PATCH => self.retry_non_idempotent,
+            _ => true,
+        }
+    }
+
+    /// Marks a request that only reads data despite its method, e.g. a GraphQL query that is
+    /// sent with `POST`. It is retried like a `GET` request and not serialized with mutations.
+    pub(super) fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub(super) fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub(super) fn api_version_or<'a>(&'a self, default: &'a ApiVersion) -> &'a ApiVersion {
+        self.api_version.as_ref().unwrap_or(default)
+    }
+
+    pub(super) fn accept(&self) -> &str {
+        self.media_type.accept()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Method;
+
+    use super::{ApiVersion, MediaType, RequestOptions};
+
+    #[test]
+    fn is_retryable_requires_opt_in_for_post_and_patch() {
+        let options = RequestOptions::default();
+
+        assert!(options.is_retryable(&Method::GET));
+        assert!(options.is_retryable(&Method::PUT));
+        assert!(options.is_retryable(&Method::DELETE));
+        assert!(!options.is_retryable(&Method::POST));
+        assert!(!options.is_retryable(&Method::PATCH));
+
+        let options = options.retry_non_idempotent(true);
+
+        assert!(options.is_retryable(&Method::POST));
+        assert!(options.is_retryable(&Method::PATCH));
+    }
+
+    #[test]
+    fn is_retryable_for_read_only_post() {
+        let options = RequestOptions::default().read_only(true);
+
+        assert!(options.is_retryable(&Method::POST));
+    }
+
+    #[test]
+    fn api_version_overrides_default() {
+        let default = ApiVersion::default();
+
+        let options = RequestOptions::default();
+        assert_eq!("2022-11-28", options.api_version_or(&default).get());
+
+        let options = options.api_version(ApiVersion::new("2026-03-10"));
+        assert_eq!("2026-03-10", options.api_version_or(&default).get());
+    }
+
+    #[test]
+    fn media_type_sets_accept_header() {
+        assert_eq!(
+            "application/vnd.github+json",
+            RequestOptions::default().accept()
+        );
+        assert_eq!(
+            "application/vnd.github.diff",
+            RequestOptions::default()
+                .media_type(MediaType::Diff)
+                .accept()
+        );
+        assert_eq!(
+            "application/vnd.github.squirrel-girl-preview+json",
+            MediaType::Custom("application/vnd.github.squirrel-girl-preview+json".into()).accept()
+        );
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<RequestOptions>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<RequestOptions>();
+    }
+}
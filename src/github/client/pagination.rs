@@ -0,0 +1,368 @@
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use anyhow::Context as _;
+use futures::stream::{self, BoxStream};
+use futures::{Stream, StreamExt};
+use parking_lot::Mutex;
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use url::Url;
+
+use crate::github::client::{GitHubClient, GitHubClientError, RequestOptions};
+use crate::github::GitHubHost;
+
+/// Describes which entities of a paginated endpoint to fetch.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PaginationRequest {
+    method: Method,
+    endpoint: String,
+    key: String,
+    query: Vec<(String, String)>,
+    per_page: Option<u8>,
+    max_items: Option<usize>,
+    options: RequestOptions,
+}
+
+impl PaginationRequest {
+    /// Creates a `GET` request for the endpoint, which reads the entities from the field `key` of
+    /// each page. Endpoints that return a bare array ignore the key.
+    pub fn new(endpoint: impl Into<String>, key: impl Into<String>) -> Self {
+        Self {
+            method: Method::GET,
+            endpoint: endpoint.into(),
+            key: key.into(),
+            query: Vec::new(),
+            per_page: None,
+            max_items: None,
+            options: RequestOptions::default(),
+        }
+    }
+
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Adds a query parameter, e.g. a filter, to the request for the first page.
+    pub fn query(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the number of entities per page. GitHub allows at most 100.
+    pub fn per_page(mut self, per_page: u8) -> Self {
+        self.per_page = Some(per_page.clamp(1, 100));
+        self
+    }
+
+    /// Stops fetching pages once this many entities have been returned.
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Sends every page with the options, e.g. to request a different media type.
+    pub fn options(mut self, options: RequestOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the URL of the first page. Subsequent pages are read from the `link` header,
+    /// which preserves the query parameters.
+    pub(super) fn url(&self, github_host: &GitHubHost) -> Result<Url, GitHubClientError> {
+        let mut url = Url::parse(&github_host.rest_url(&self.endpoint))
+            .context("failed to parse URL of paginated endpoint")?;
+
+        if !self.query.is_empty() || self.per_page.is_some() {
+            let mut pairs = url.query_pairs_mut();
+
+            for (name, value) in &self.query {
+                pairs.append_pair(name, value);
+            }
+
+            if let Some(per_page) = self.per_page {
+                pairs.append_pair("per_page", &per_page.to_string());
+            }
+        }
+
+        Ok(url)
+    }
+}
+
+/// A single page of a paginated endpoint.
+#[derive(Debug)]
+pub(super) struct Page<T> {
+    pub entities: Vec<T>,
+    pub next_url: Option<String>,
+    pub total_count: Option<u64>,
+}
+
+/// A stream of the entities of a paginated endpoint.
+///
+/// Pages are only fetched when the entities of the previous page have been consumed, so callers
+/// can stop early without fetching the remaining pages. The stream ends after the first error.
+pub struct PageStream<T> {
+    total_count: Arc<Mutex<Option<u64>>>,
+    stream: BoxStream<'static, Result<T, GitHubClientError>>,
+}
+
+struct State<T> {
+    client: GitHubClient,
+    method: Method,
+    key: String,
+    options: RequestOptions,
+    next_url: Option<String>,
+    entities: VecDeque<T>,
+    total_count: Arc<Mutex<Option<u64>>>,
+}
+
+impl<T> PageStream<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    pub(super) fn new(client: GitHubClient, request: &PaginationRequest) -> Self {
+        let total_count = Arc::new(Mutex::new(None));
+
+        let url = match request.url(&client.github_host) {
+            Ok(url) => url,
+            Err(error) => {
+                return Self {
+                    total_count,
+                    stream: stream::once(async { Err(error) }).boxed(),
+                }
+            }
+        };
+
+        let state = State {
+            client,
+            method: request.method.clone(),
+            key: request.key.clone(),
+            options: request.options.clone(),
+            next_url: Some(url.into()),
+            entities: VecDeque::new(),
+            total_count: total_count.clone(),
+        };
+
+        let stream = stream::try_unfold(state, |mut state| async move {
+            loop {
+                if let Some(entity) = state.entities.pop_front() {
+                    return Ok(Some((entity, state)));
+                }
+
+                let url = match state.next_url.take() {
+                    Some(url) => url,
+                    None => return Ok(None),
+                };
+
+                let page = state
+                    .client
+                    .fetch_page(state.method.clone(), &url, &state.key, &state.options)
+                    .await?;
+
+                if page.total_count.is_some() {
+                    *state.total_count.lock() = page.total_count;
+                }
+
+                state.next_url = page.next_url;
+                state.entities.extend(page.entities);
+            }
+        });
+
+        let stream = match request.max_items {
+            Some(max_items) => stream.take(max_items).boxed(),
+            None => stream.boxed(),
+        };
+
+        Self {
+            total_count,
+            stream,
+        }
+    }
+}
+
+impl<T> PageStream<T> {
+    /// Returns the total number of entities, if the endpoint reports it.
+    ///
+    /// The count is only known after the first page has been fetched.
+    pub fn total_count(&self) -> Option<u64> {
+        *self.total_count.lock()
+    }
+}
+
+impl<T> Stream for PageStream<T> {
+    type Item = Result<T, GitHubClientError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
+
+impl<T> Debug for PageStream<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PageStream")
+            .field("total_count", &self.total_count())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{StreamExt, TryStreamExt};
+    use mockito::mock;
+    use reqwest::Method;
+
+    use crate::github::client::GitHubClientError;
+    use crate::github::GitHubHost;
+    use crate::repository::Repository;
+    use crate::testing::client::github_client;
+    use crate::testing::token::mock_installation_access_tokens;
+
+    use super::{PageStream, PaginationRequest};
+
+    const REPOSITORY: &str = r#"
+        {
+            "id": 1296269,
+            "name": "Hello-World",
+            "description": "This your first repo!",
+            "owner": {
+                "login": "octocat",
+                "id": 1,
+                "type": "User"
+            },
+            "visibility": "public"
+        }
+    "#;
+
+    #[tokio::test]
+    async fn stream_fetches_pages_lazily() {
+        let _token_mock = mock_installation_access_tokens();
+        let first_page_mock = mock("GET", "/orgs/lazy/repos")
+            .with_status(200)
+            .with_header(
+                "link",
+                &format!(
+                    "<{}/orgs/lazy/repos?page=2>; rel=\"next\"",
+                    mockito::server_url()
+                ),
+            )
+            .with_body(format!("[{}, {}]", REPOSITORY, REPOSITORY))
+            .expect(1)
+            .create();
+        let second_page_mock = mock("GET", "/orgs/lazy/repos?page=2")
+            .with_status(200)
+            .with_body(format!("[{}]", REPOSITORY))
+            .expect(0)
+            .create();
+
+        let stream =
+            github_client().paginate_stream::<Repository>(Method::GET, "/orgs/lazy/repos", "");
+
+        let repositories: Vec<Repository> = stream.take(2).try_collect().await.unwrap();
+
+        first_page_mock.assert();
+        second_page_mock.assert();
+        assert_eq!(2, repositories.len());
+    }
+
+    #[tokio::test]
+    async fn stream_reports_total_count() {
+        let _token_mock = mock_installation_access_tokens();
+        let _page_mock = mock("GET", "/installation/repositories?stream=1")
+            .with_status(200)
+            .with_body(format!(
+                r#"{{ "total_count": 1, "repositories": [{}] }}"#,
+                REPOSITORY
+            ))
+            .create();
+
+        let mut stream: PageStream<Repository> = github_client().paginate_stream(
+            Method::GET,
+            "/installation/repositories?stream=1",
+            "repositories",
+        );
+
+        assert_eq!(None, stream.total_count());
+
+        let repository = stream.next().await.unwrap().unwrap();
+
+        assert_eq!(1296269, repository.id().get());
+        assert_eq!(Some(1), stream.total_count());
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn request_sends_query_and_stops_at_max_items() {
+        let _token_mock = mock_installation_access_tokens();
+        let page_mock = mock("GET", "/orgs/github/repos?type=public&per_page=100")
+            .with_status(200)
+            .with_header(
+                "link",
+                &format!(
+                    "<{}/orgs/github/repos?type=public&per_page=100&page=2>; rel=\"next\"",
+                    mockito::server_url()
+                ),
+            )
+            .with_body(format!("[{}, {}, {}]", REPOSITORY, REPOSITORY, REPOSITORY))
+            .expect(1)
+            .create();
+
+        let request = PaginationRequest::new("/orgs/github/repos", "")
+            .query("type", "public")
+            .per_page(100)
+            .max_items(2);
+
+        let repositories: Vec<Repository> = github_client().paginate_with(&request).await.unwrap();
+
+        page_mock.assert();
+        assert_eq!(2, repositories.len());
+    }
+
+    #[tokio::test]
+    async fn request_fails_with_status_of_page() {
+        let _token_mock = mock_installation_access_tokens();
+        let _first_page_mock = mock("GET", "/orgs/failing/repos")
+            .with_status(200)
+            .with_header(
+                "link",
+                &format!(
+                    "<{}/orgs/failing/repos?page=2>; rel=\"next\"",
+                    mockito::server_url()
+                ),
+            )
+            .with_body(format!("[{}]", REPOSITORY))
+            .create();
+        let _second_page_mock = mock("GET", "/orgs/failing/repos?page=2")
+            .with_status(404)
+            .with_body(r#"{ "message": "Not Found" }"#)
+            .create();
+
+        let error = github_client()
+            .paginate_with::<Repository>(&PaginationRequest::new("/orgs/failing/repos", ""))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, GitHubClientError::NotFound(_)));
+    }
+
+    #[test]
+    fn per_page_is_limited_to_maximum() {
+        let request = PaginationRequest::new("/orgs/github/repos", "").per_page(255);
+
+        let url = request.url(&GitHubHost::github_com()).unwrap();
+
+        assert_eq!(
+            "https://api.github.com/orgs/github/repos?per_page=100",
+            url.as_str()
+        );
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<PageStream<Repository>>();
+    }
+}
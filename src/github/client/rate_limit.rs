@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeZone, Utc};
+use derive_new::new;
+use getset::CopyGetters;
+use parking_lot::Mutex;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::github::client::GitHubClientError;
+use crate::name;
+
+name!(RateLimitResource);
+
+/// The state of a rate limit, as reported by GitHub in the `x-ratelimit-*` headers.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Deserialize, Serialize, CopyGetters, new)]
+pub struct RateLimit {
+    #[getset(get_copy = "pub")]
+    limit: u64,
+
+    #[getset(get_copy = "pub")]
+    remaining: u64,
+
+    #[getset(get_copy = "pub")]
+    used: u64,
+
+    #[getset(get_copy = "pub")]
+    #[serde(with = "chrono::serde::ts_seconds")]
+    reset: DateTime<Utc>,
+}
+
+impl RateLimit {
+    /// Checks if the rate limit has no requests left before it resets.
+    pub fn is_exhausted(&self, now: DateTime<Utc>) -> bool {
+        self.remaining == 0 && self.reset > now
+    }
+
+    fn from_headers(headers: &HeaderMap) -> Option<(RateLimitResource, RateLimit)> {
+        let header = |name: &str| -> Option<u64> { headers.get(name)?.to_str().ok()?.parse().ok() };
+
+        let rate_limit = RateLimit {
+            limit: header("x-ratelimit-limit")?,
+            remaining: header("x-ratelimit-remaining")?,
+            used: header("x-ratelimit-used").unwrap_or_default(),
+            reset: Utc
+                .timestamp_opt(header("x-ratelimit-reset")? as i64, 0)
+                .single()?,
+        };
+
+        let resource = headers
+            .get("x-ratelimit-resource")
+            .and_then(|header| header.to_str().ok())
+            .unwrap_or("core");
+
+        Some((RateLimitResource::new(resource), rate_limit))
+    }
+}
+
+/// Decides what the client does when a rate limit has been exhausted.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum RateLimitPolicy {
+    /// Waits until the rate limit resets, and then sends the request.
+    #[default]
+    Wait,
+
+    /// Returns [`GitHubClientError::RateLimited`] without sending the request.
+    FailFast,
+}
+
+/// Tracks the rate limits of a client across all of its clones.
+#[derive(Clone, Debug, Default)]
+pub(super) struct RateLimiter {
+    policy: RateLimitPolicy,
+    limits: Arc<Mutex<HashMap<RateLimitResource, RateLimit>>>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter that records the rate limits in the given state, which other limiters
+    /// might share.
+    pub fn with_limits(limits: Arc<Mutex<HashMap<RateLimitResource, RateLimit>>>) -> Self {
+        Self {
+            policy: RateLimitPolicy::default(),
+            limits,
+        }
+    }
+
+    pub fn policy(&self) -> RateLimitPolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: RateLimitPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn get(&self, resource: &RateLimitResource) -> Option<RateLimit> {
+        self.limits.lock().get(resource).copied()
+    }
+
+    pub fn snapshot(&self) -> HashMap<RateLimitResource, RateLimit> {
+        self.limits.lock().clone()
+    }
+
+    /// Checks if the rate limit of the resource allows sending a request, and returns how long
+    /// the client has to wait before sending it. Fails if the policy does not allow waiting.
+    pub fn check(
+        &self,
+        resource: &RateLimitResource,
+    ) -> Result<Option<std::time::Duration>, GitHubClientError> {
+        let rate_limit = match self.get(resource) {
+            Some(rate_limit) if rate_limit.is_exhausted(Utc::now()) => rate_limit,
+            _ => return Ok(None),
+        };
+
+        match self.policy {
+            RateLimitPolicy::FailFast => Err(GitHubClientError::RateLimited(rate_limit.reset())),
+            RateLimitPolicy::Wait => {
+                // GitHub's clock and ours are not perfectly in sync, so we wait a little longer
+                // to avoid sending the request just before the reset.
+                let wait = (rate_limit.reset() - Utc::now() + chrono::Duration::seconds(1))
+                    .to_std()
+                    .unwrap_or_default();
+
+                tracing::warn!(
+                    "rate limit for {} is exhausted, waiting {} seconds until it resets",
+                    resource,
+                    wait.as_secs()
+                );
+
+                Ok(Some(wait))
+            }
+        }
+    }
+
+    /// Records the rate limit of a response, and returns the time at which it resets if the
+    /// response was rejected because the rate limit was exhausted.
+    pub fn record(&self, status: StatusCode, headers: &HeaderMap) -> Option<DateTime<Utc>> {
+        let (resource, rate_limit) = RateLimit::from_headers(headers)?;
+
+        self.limits.lock().insert(resource, rate_limit);
+
+        let rejected = status == StatusCode::FORBIDDEN || status == StatusCode::TOO_MANY_REQUESTS;
+        (rejected && rate_limit.remaining() == 0).then(|| rate_limit.reset())
+    }
+}
+
+/// Returns the rate limit that applies to the endpoint, based on GitHub's documentation.
+pub(super) fn resource_for_endpoint(path: &str) -> Option<RateLimitResource> {
+    // Requests to the rate limit endpoint do not count against any rate limit.
+    if path.ends_with("/rate_limit") {
+        return None;
+    }
+
+    let resource = if path.contains("/search/code") {
+        "code_search"
+    } else if path.contains("/search/") {
+        "search"
+    } else if path.ends_with("/graphql") {
+        "graphql"
+    } else {
+        "core"
+    };
+
+    Some(RateLimitResource::new(resource))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use reqwest::StatusCode;
+
+    use crate::github::client::GitHubClientError;
+
+    use super::{
+        resource_for_endpoint, RateLimit, RateLimitPolicy, RateLimitResource, RateLimiter,
+    };
+
+    fn headers(remaining: u64, reset: i64) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        headers.insert("x-ratelimit-limit", HeaderValue::from(5000));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from(remaining));
+        headers.insert("x-ratelimit-used", HeaderValue::from(5000 - remaining));
+        headers.insert("x-ratelimit-reset", HeaderValue::from(reset));
+        headers.insert("x-ratelimit-resource", HeaderValue::from_static("core"));
+
+        headers
+    }
+
+    #[test]
+    fn record_stores_rate_limit() {
+        let limiter = RateLimiter::default();
+        let reset = Utc::now().timestamp() + 60;
+
+        let rejected = limiter.record(StatusCode::OK, &headers(4999, reset));
+
+        let rate_limit = limiter.get(&RateLimitResource::new("core")).unwrap();
+        assert!(rejected.is_none());
+        assert_eq!(4999, rate_limit.remaining());
+        assert_eq!(1, rate_limit.used());
+        assert_eq!(reset, rate_limit.reset().timestamp());
+    }
+
+    #[test]
+    fn record_detects_rejected_request() {
+        let limiter = RateLimiter::default();
+        let reset = Utc::now().timestamp() + 60;
+
+        let rejected = limiter.record(StatusCode::FORBIDDEN, &headers(0, reset));
+
+        assert_eq!(Some(reset), rejected.map(|reset| reset.timestamp()));
+    }
+
+    #[test]
+    fn record_ignores_response_without_headers() {
+        let limiter = RateLimiter::default();
+
+        limiter.record(StatusCode::OK, &HeaderMap::new());
+
+        assert!(limiter.snapshot().is_empty());
+    }
+
+    #[test]
+    fn check_fails_fast_when_exhausted() {
+        let mut limiter = RateLimiter::default();
+        limiter.set_policy(RateLimitPolicy::FailFast);
+        limiter.record(StatusCode::OK, &headers(0, Utc::now().timestamp() + 60));
+
+        let error = limiter.check(&RateLimitResource::new("core")).unwrap_err();
+
+        assert!(matches!(error, GitHubClientError::RateLimited(_)));
+    }
+
+    #[test]
+    fn check_waits_until_reset() {
+        let limiter = RateLimiter::default();
+        limiter.record(StatusCode::OK, &headers(0, Utc::now().timestamp() + 60));
+
+        let wait = limiter
+            .check(&RateLimitResource::new("core"))
+            .unwrap()
+            .unwrap();
+
+        assert!(wait.as_secs() > 55);
+    }
+
+    #[test]
+    fn check_succeeds_after_reset() {
+        let mut limiter = RateLimiter::default();
+        limiter.set_policy(RateLimitPolicy::FailFast);
+        limiter.record(StatusCode::OK, &headers(0, Utc::now().timestamp() - 1));
+
+        assert_eq!(
+            None,
+            limiter.check(&RateLimitResource::new("core")).unwrap()
+        );
+    }
+
+    #[test]
+    fn is_exhausted_requires_future_reset() {
+        let now = Utc::now();
+
+        assert!(RateLimit::new(5000, 0, 5000, now + Duration::minutes(1)).is_exhausted(now));
+        assert!(!RateLimit::new(5000, 0, 5000, now - Duration::minutes(1)).is_exhausted(now));
+        assert!(!RateLimit::new(5000, 1, 4999, now + Duration::minutes(1)).is_exhausted(now));
+    }
+
+    #[test]
+    fn resource_for_endpoint_maps_endpoints() {
+        assert_eq!(
+            Some(RateLimitResource::new("core")),
+            resource_for_endpoint("/repos/octocat/Hello-World")
+        );
+        assert_eq!(
+            Some(RateLimitResource::new("search")),
+            resource_for_endpoint("/search/issues")
+        );
+        assert_eq!(
+            Some(RateLimitResource::new("code_search")),
+            resource_for_endpoint("/search/code")
+        );
+        assert_eq!(None, resource_for_endpoint("/rate_limit"));
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<RateLimit>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<RateLimit>();
+    }
+}
@@ -0,0 +1,49 @@
+use getset::{CopyGetters, Getters};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+
+/// A response that the client has not interpreted, returned by [`GitHubClient::request`].
+///
+/// [`GitHubClient::request`]: crate::github::client::GitHubClient::request
+#[derive(Clone, Debug, CopyGetters, Getters)]
+pub struct RawResponse {
+    #[getset(get_copy = "pub")]
+    status: StatusCode,
+
+    #[getset(get = "pub")]
+    headers: HeaderMap,
+
+    #[getset(get = "pub")]
+    body: Vec<u8>,
+}
+
+impl RawResponse {
+    pub fn new(status: StatusCode, headers: HeaderMap, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    pub fn into_body(self) -> Vec<u8> {
+        self.body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RawResponse;
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<RawResponse>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<RawResponse>();
+    }
+}
@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use derive_new::new;
+use getset::CopyGetters;
+use rand::Rng;
+use reqwest::StatusCode;
+
+use crate::github::client::GitHubClientError;
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_INITIAL_BACKOFF_MILLISECONDS: u64 = 500;
+const DEFAULT_MAX_BACKOFF_SECONDS: u64 = 10;
+
+/// Decides how often and how long the client retries requests that failed with a transient error,
+/// e.g. a network error or a `502 Bad Gateway`.
+///
+/// The client waits a random duration between zero and an exponentially growing backoff before
+/// each retry, so that clients that failed at the same time do not retry at the same time.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, CopyGetters, new)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    #[getset(get_copy = "pub")]
+    max_attempts: u32,
+
+    #[getset(get_copy = "pub")]
+    initial_backoff: Duration,
+
+    #[getset(get_copy = "pub")]
+    max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Creates a policy that never retries a request.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        }
+    }
+
+    /// Returns how long to wait after the given attempt failed, or `None` if the client should
+    /// give up.
+    pub fn delay(&self, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+
+        let jitter = rand::thread_rng().gen_range(0..=backoff.as_millis() as u64);
+
+        Some(Duration::from_millis(jitter))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: Duration::from_millis(DEFAULT_INITIAL_BACKOFF_MILLISECONDS),
+            max_backoff: Duration::from_secs(DEFAULT_MAX_BACKOFF_SECONDS),
+        }
+    }
+}
+
+/// Checks if a request failed with an error that might go away when the request is retried.
+pub(super) fn is_transient_error(error: &GitHubClientError) -> bool {
+    match error {
+        GitHubClientError::Request(error) => {
+            error.is_connect() || error.is_timeout() || error.is_request()
+        }
+        _ => false,
+    }
+}
+
+pub(super) fn is_transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::StatusCode;
+
+    use super::{is_transient_status, RetryPolicy};
+
+    #[test]
+    fn delay_is_within_backoff() {
+        let policy = RetryPolicy::new(5, Duration::from_millis(100), Duration::from_millis(300));
+
+        assert!(policy.delay(1).unwrap() <= Duration::from_millis(100));
+        assert!(policy.delay(2).unwrap() <= Duration::from_millis(200));
+        assert!(policy.delay(4).unwrap() <= Duration::from_millis(300));
+    }
+
+    #[test]
+    fn delay_gives_up_after_max_attempts() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(100), Duration::from_secs(1));
+
+        assert!(policy.delay(2).is_some());
+        assert!(policy.delay(3).is_none());
+        assert!(RetryPolicy::none().delay(1).is_none());
+    }
+
+    #[test]
+    fn is_transient_status_matches_gateway_errors() {
+        assert!(is_transient_status(StatusCode::BAD_GATEWAY));
+        assert!(is_transient_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_transient_status(StatusCode::GATEWAY_TIMEOUT));
+        assert!(!is_transient_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_transient_status(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<RetryPolicy>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<RetryPolicy>();
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use derive_new::new;
+use futures::lock::{Mutex, MutexGuard};
+use getset::CopyGetters;
+use reqwest::Method;
+
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_BACKOFF_SECONDS: u64 = 60;
+
+/// Decides how often and how long the client backs off when it hits a secondary rate limit.
+///
+/// GitHub asks clients to wait for the duration in the `retry-after` header, or at least one
+/// minute if the header is missing, and to increase the wait exponentially for repeated failures.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, CopyGetters, new)]
+pub struct SecondaryRateLimitPolicy {
+    #[getset(get_copy = "pub")]
+    max_retries: u32,
+
+    #[getset(get_copy = "pub")]
+    backoff: Duration,
+}
+
+impl SecondaryRateLimitPolicy {
+    /// Returns how long to wait before the given retry, or `None` if the client should give up.
+    pub fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if retry >= self.max_retries {
+            return None;
+        }
+
+        Some(retry_after.unwrap_or_else(|| self.backoff.saturating_mul(2u32.saturating_pow(retry))))
+    }
+}
+
+impl Default for SecondaryRateLimitPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            backoff: Duration::from_secs(DEFAULT_BACKOFF_SECONDS),
+        }
+    }
+}
+
+/// Throttles the requests of a client across all of its clones.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Throttle {
+    policy: SecondaryRateLimitPolicy,
+    mutations: Arc<Mutex<()>>,
+    throttled: Arc<AtomicU64>,
+}
+
+impl Throttle {
+    /// Creates a throttle that serializes mutating requests with every other throttle that
+    /// shares the lock.
+    pub fn with_mutations(mutations: Arc<Mutex<()>>) -> Self {
+        Self {
+            mutations,
+            ..Self::default()
+        }
+    }
+
+    pub fn policy(&self) -> SecondaryRateLimitPolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: SecondaryRateLimitPolicy) {
+        self.policy = policy;
+    }
+
+    /// Waits until no other mutating request is in flight.
+    ///
+    /// GitHub recommends sending requests that create, update or delete resources serially to
+    /// avoid its secondary rate limits. Reading requests are not serialized.
+    pub async fn serialize(&self, method: &Method) -> Option<MutexGuard<'_, ()>> {
+        let mutating = !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+
+        if mutating {
+            Some(self.mutations.lock().await)
+        } else {
+            None
+        }
+    }
+
+    /// Waits for the given duration and records that a request was throttled.
+    pub async fn throttle(&self, delay: Duration) {
+        self.throttled.fetch_add(1, Ordering::Relaxed);
+
+        tokio::time::sleep(delay).await;
+    }
+
+    pub fn throttled(&self) -> u64 {
+        self.throttled.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::Method;
+
+    use super::{SecondaryRateLimitPolicy, Throttle};
+
+    #[test]
+    fn delay_prefers_retry_after() {
+        let policy = SecondaryRateLimitPolicy::default();
+
+        let delay = policy.delay(2, Some(Duration::from_secs(5)));
+
+        assert_eq!(Some(Duration::from_secs(5)), delay);
+    }
+
+    #[test]
+    fn delay_backs_off_exponentially() {
+        let policy = SecondaryRateLimitPolicy::new(3, Duration::from_secs(60));
+
+        assert_eq!(Some(Duration::from_secs(60)), policy.delay(0, None));
+        assert_eq!(Some(Duration::from_secs(120)), policy.delay(1, None));
+        assert_eq!(Some(Duration::from_secs(240)), policy.delay(2, None));
+        assert_eq!(None, policy.delay(3, None));
+    }
+
+    #[tokio::test]
+    async fn serialize_locks_mutating_requests() {
+        let throttle = Throttle::default();
+
+        let guard = throttle.serialize(&Method::POST).await;
+
+        assert!(guard.is_some());
+        assert!(throttle.serialize(&Method::GET).await.is_none());
+        assert!(throttle.mutations.try_lock().is_none());
+    }
+
+    #[tokio::test]
+    async fn throttle_counts_requests() {
+        let throttle = Throttle::default();
+
+        throttle.throttle(Duration::ZERO).await;
+        throttle.clone().throttle(Duration::ZERO).await;
+
+        assert_eq!(2, throttle.throttled());
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<SecondaryRateLimitPolicy>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<SecondaryRateLimitPolicy>();
+    }
+}
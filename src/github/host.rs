@@ -0,0 +1,351 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+const GITHUB_COM_REST: &str = "https://api.github.com";
+const GITHUB_COM_UPLOADS: &str = "https://uploads.github.com";
+const GITHUB_COM_WEB: &str = "https://github.com";
+const DATA_RESIDENCY_DOMAIN: &str = ".ghe.com";
+
+/// The base URLs of a GitHub instance: github.com, GitHub Enterprise Server (GHES), or GitHub
+/// Enterprise Cloud with data residency (GHE.com).
+///
+/// The REST, uploads, and GraphQL APIs live at different bases depending on the product, e.g.
+/// `https://api.github.com` on github.com and `https://ghes.example.com/api/v3` on GHES.
+///
+/// A host is (de)serialized as the base URL of its REST API, and invalid URLs are rejected when
+/// a configuration is deserialized.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct GitHubHost {
+    rest: Url,
+    uploads: Url,
+    graphql: Url,
+    web: Url,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GitHubHostError {
+    #[error("failed to parse GitHub host {0}")]
+    InvalidUrl(String, #[source] url::ParseError),
+
+    #[error("GitHub host {0} must be an http or https URL without credentials, query or fragment")]
+    UnsupportedUrl(String),
+
+    #[error("GitHub host {0} is not a GHE.com host")]
+    NotDataResidency(String),
+}
+
+impl GitHubHost {
+    /// Creates a host from the base URL of its REST API.
+    ///
+    /// The other base URLs are derived from the REST API for github.com, GHES (a base URL that
+    /// ends with `/api/v3`) and GHE.com. Any other URL, e.g. a proxy or a mock server, is used
+    /// as the base of every API.
+    pub fn new(rest_url: &str) -> Result<Self, GitHubHostError> {
+        let url = parse(rest_url)?;
+        let host = url.host_str().unwrap_or_default();
+        let path = url.path().trim_end_matches('/');
+
+        if host == "api.github.com" && path.is_empty() {
+            return Ok(Self::github_com());
+        }
+
+        if let Some(base) = path.strip_suffix("/api/v3") {
+            let mut web = url.clone();
+            web.set_path(base);
+
+            return Self::enterprise_server(web.as_str());
+        }
+
+        if let Some(subdomain) = host.strip_prefix("api.") {
+            if subdomain.ends_with(DATA_RESIDENCY_DOMAIN) && path.is_empty() {
+                return Self::data_residency(&format!("https://{}", subdomain));
+            }
+        }
+
+        Ok(Self {
+            graphql: join(&url, "/graphql")?,
+            uploads: url.clone(),
+            web: url.clone(),
+            rest: url,
+        })
+    }
+
+    pub fn github_com() -> Self {
+        Self {
+            rest: Url::parse(GITHUB_COM_REST).expect("failed to parse github.com REST URL"),
+            uploads: Url::parse(GITHUB_COM_UPLOADS)
+                .expect("failed to parse github.com uploads URL"),
+            graphql: Url::parse(&format!("{}/graphql", GITHUB_COM_REST))
+                .expect("failed to parse github.com GraphQL URL"),
+            web: Url::parse(GITHUB_COM_WEB).expect("failed to parse github.com web URL"),
+        }
+    }
+
+    /// Creates a host for a GitHub Enterprise Server from the URL of its web interface, e.g.
+    /// `https://ghes.example.com`.
+    pub fn enterprise_server(web_url: &str) -> Result<Self, GitHubHostError> {
+        let web = parse(web_url)?;
+
+        Ok(Self {
+            rest: join(&web, "/api/v3")?,
+            uploads: join(&web, "/api/uploads")?,
+            graphql: join(&web, "/api/graphql")?,
+            web,
+        })
+    }
+
+    /// Creates a host for a GHE.com enterprise from the URL of its web interface, e.g.
+    /// `https://octocorp.ghe.com`.
+    pub fn data_residency(web_url: &str) -> Result<Self, GitHubHostError> {
+        let web = parse(web_url)?;
+        let host = web.host_str().unwrap_or_default();
+
+        if !host.ends_with(DATA_RESIDENCY_DOMAIN) || !web.path().trim_matches('/').is_empty() {
+            return Err(GitHubHostError::NotDataResidency(web_url.into()));
+        }
+
+        let subdomain = |name: &str| parse(&format!("{}://{}.{}", web.scheme(), name, host));
+        let rest = subdomain("api")?;
+
+        Ok(Self {
+            graphql: join(&rest, "/graphql")?,
+            uploads: subdomain("uploads")?,
+            rest,
+            web,
+        })
+    }
+
+    /// Returns the base URL of the REST API without a trailing slash, e.g.
+    /// `https://api.github.com`.
+    pub fn get(&self) -> &str {
+        base(&self.rest)
+    }
+
+    pub fn rest(&self) -> &Url {
+        &self.rest
+    }
+
+    pub fn uploads(&self) -> &Url {
+        &self.uploads
+    }
+
+    pub fn graphql(&self) -> &Url {
+        &self.graphql
+    }
+
+    pub fn web(&self) -> &Url {
+        &self.web
+    }
+
+    /// Returns the URL of an endpoint of the REST API, e.g. `/repos/octocat/Hello-World`.
+    pub fn rest_url(&self, endpoint: &str) -> String {
+        format!("{}{}", base(&self.rest), endpoint)
+    }
+
+    pub fn uploads_url(&self, endpoint: &str) -> String {
+        format!("{}{}", base(&self.uploads), endpoint)
+    }
+
+    /// Returns the URL of a page of the web interface, e.g. `/login/oauth/access_token`.
+    pub fn web_url(&self, path: &str) -> String {
+        format!("{}{}", base(&self.web), path)
+    }
+
+    /// Returns the endpoint of a URL of the REST API, or the URL itself if it belongs to another
+    /// host.
+    pub(crate) fn rest_endpoint<'a>(&self, url: &'a str) -> &'a str {
+        url.strip_prefix(base(&self.rest)).unwrap_or(url)
+    }
+}
+
+impl Display for GitHubHost {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get())
+    }
+}
+
+impl FromStr for GitHubHost {
+    type Err = GitHubHostError;
+
+    fn from_str(rest_url: &str) -> Result<Self, Self::Err> {
+        Self::new(rest_url)
+    }
+}
+
+impl TryFrom<&str> for GitHubHost {
+    type Error = GitHubHostError;
+
+    fn try_from(rest_url: &str) -> Result<Self, Self::Error> {
+        Self::new(rest_url)
+    }
+}
+
+impl TryFrom<String> for GitHubHost {
+    type Error = GitHubHostError;
+
+    fn try_from(rest_url: String) -> Result<Self, Self::Error> {
+        Self::new(&rest_url)
+    }
+}
+
+impl From<GitHubHost> for String {
+    fn from(host: GitHubHost) -> Self {
+        host.to_string()
+    }
+}
+
+fn parse(url: &str) -> Result<Url, GitHubHostError> {
+    let parsed =
+        Url::parse(url.trim()).map_err(|error| GitHubHostError::InvalidUrl(url.into(), error))?;
+
+    let is_supported = matches!(parsed.scheme(), "http" | "https")
+        && parsed.host_str().is_some()
+        && parsed.username().is_empty()
+        && parsed.password().is_none()
+        && parsed.query().is_none()
+        && parsed.fragment().is_none();
+
+    if !is_supported {
+        return Err(GitHubHostError::UnsupportedUrl(url.into()));
+    }
+
+    Ok(parsed)
+}
+
+fn join(url: &Url, path: &str) -> Result<Url, GitHubHostError> {
+    parse(&format!("{}{}", base(url), path))
+}
+
+/// Returns the URL without a trailing slash, so that endpoints can be appended to it.
+fn base(url: &Url) -> &str {
+    url.as_str().trim_end_matches('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GitHubHost, GitHubHostError};
+
+    #[test]
+    fn github_com() {
+        let host = GitHubHost::github_com();
+
+        assert_eq!(
+            "https://api.github.com/repos/octocat/Hello-World",
+            host.rest_url("/repos/octocat/Hello-World")
+        );
+        assert_eq!("https://uploads.github.com/", host.uploads().as_str());
+        assert_eq!("https://api.github.com/graphql", host.graphql().as_str());
+        assert_eq!("https://github.com/", host.web().as_str());
+        assert_eq!(host, GitHubHost::new("https://api.github.com/").unwrap());
+    }
+
+    #[test]
+    fn enterprise_server() {
+        let host = GitHubHost::enterprise_server("https://ghes.example.com").unwrap();
+
+        assert_eq!(
+            "https://ghes.example.com/api/v3/repos/octocat/Hello-World",
+            host.rest_url("/repos/octocat/Hello-World")
+        );
+        assert_eq!(
+            "https://ghes.example.com/api/uploads",
+            host.uploads().as_str()
+        );
+        assert_eq!(
+            "https://ghes.example.com/api/graphql",
+            host.graphql().as_str()
+        );
+        assert_eq!(
+            "https://ghes.example.com/login/oauth/access_token",
+            host.web_url("/login/oauth/access_token")
+        );
+        assert_eq!(
+            host,
+            GitHubHost::new("https://ghes.example.com/api/v3").unwrap()
+        );
+    }
+
+    #[test]
+    fn data_residency() {
+        let host = GitHubHost::data_residency("https://octocorp.ghe.com").unwrap();
+
+        assert_eq!("https://api.octocorp.ghe.com/", host.rest().as_str());
+        assert_eq!("https://uploads.octocorp.ghe.com/", host.uploads().as_str());
+        assert_eq!(
+            "https://api.octocorp.ghe.com/graphql",
+            host.graphql().as_str()
+        );
+        assert_eq!("https://octocorp.ghe.com/", host.web().as_str());
+        assert_eq!(
+            host,
+            GitHubHost::new("https://api.octocorp.ghe.com").unwrap()
+        );
+    }
+
+    #[test]
+    fn data_residency_rejects_other_hosts() {
+        let error = GitHubHost::data_residency("https://ghes.example.com").unwrap_err();
+
+        assert!(matches!(error, GitHubHostError::NotDataResidency(_)));
+    }
+
+    #[test]
+    fn new_uses_custom_url_for_every_api() {
+        let host = GitHubHost::new("http://127.0.0.1:1234").unwrap();
+
+        assert_eq!("http://127.0.0.1:1234/app", host.rest_url("/app"));
+        assert_eq!("http://127.0.0.1:1234/graphql", host.graphql().as_str());
+        assert_eq!("http://127.0.0.1:1234", host.to_string());
+        assert_eq!("/app", host.rest_endpoint("http://127.0.0.1:1234/app"));
+    }
+
+    #[test]
+    fn new_rejects_malformed_hosts() {
+        assert!(matches!(
+            GitHubHost::new("api.github.com"),
+            Err(GitHubHostError::InvalidUrl(_, _))
+        ));
+        assert!(matches!(
+            GitHubHost::new("ftp://github.com"),
+            Err(GitHubHostError::UnsupportedUrl(_))
+        ));
+        assert!(matches!(
+            GitHubHost::new("https://api.github.com?page=1"),
+            Err(GitHubHostError::UnsupportedUrl(_))
+        ));
+    }
+
+    #[test]
+    fn serde_round_trips_rest_url() {
+        let host = GitHubHost::enterprise_server("https://ghes.example.com").unwrap();
+
+        let json = serde_json::to_string(&host).unwrap();
+        assert_eq!(r#""https://ghes.example.com/api/v3""#, json);
+
+        let deserialized: GitHubHost = serde_json::from_str(&json).unwrap();
+        assert_eq!(host, deserialized);
+        assert_eq!("https://ghes.example.com/api/v3", deserialized.get());
+    }
+
+    #[test]
+    fn deserialize_rejects_malformed_hosts() {
+        assert!(serde_json::from_str::<GitHubHost>(r#""ftp://github.com""#).is_err());
+        assert!(serde_json::from_str::<GitHubHost>(r#""api.github.com""#).is_err());
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<GitHubHost>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<GitHubHost>();
+    }
+}
@@ -0,0 +1,149 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, Proxy};
+
+const DEFAULT_CONNECT_TIMEOUT_SECONDS: u64 = 10;
+const DEFAULT_TIMEOUT_SECONDS: u64 = 30;
+const DEFAULT_USER_AGENT: &str = "devxbots/github-parts";
+
+static DEFAULT_HTTP_CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// Configures the HTTP client that sends requests to GitHub.
+///
+/// The client keeps a pool of connections, so it should be built once and shared by every
+/// [`GitHubClient`] and [`TokenFactory`] in the process.
+///
+/// [`GitHubClient`]: crate::github::client::GitHubClient
+/// [`TokenFactory`]: crate::github::token::TokenFactory
+#[derive(Clone, Debug)]
+pub struct HttpClientConfig {
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    user_agent: String,
+    proxy: Option<Proxy>,
+    default_headers: HeaderMap,
+}
+
+impl HttpClientConfig {
+    /// Limits how long the client waits for a connection to be established.
+    pub fn connect_timeout(mut self, connect_timeout: Option<Duration>) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Limits how long a request may take, from connecting until the body has been read.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Identifies the application to GitHub, which requires every request to have a user agent.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Adds a header to every request. Headers that the client sets itself take precedence.
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.default_headers.insert(name, value);
+        self
+    }
+
+    pub fn build(&self) -> Result<Client, reqwest::Error> {
+        let mut builder = Client::builder()
+            .user_agent(self.user_agent.as_str())
+            .default_headers(self.default_headers.clone());
+
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+
+        builder.build()
+    }
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Some(Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECONDS)),
+            timeout: Some(Duration::from_secs(DEFAULT_TIMEOUT_SECONDS)),
+            user_agent: DEFAULT_USER_AGENT.into(),
+            proxy: None,
+            default_headers: HeaderMap::new(),
+        }
+    }
+}
+
+/// Returns the given client, or the client with the default configuration if none was given.
+///
+/// The default client is built once, when it is first needed, and shared by every
+/// [`GitHubClient`], [`TokenFactory`] and [`UserAuthenticator`] without a client of their own.
+/// Building it only fails if the TLS backend cannot be initialized, and then the next call tries
+/// again.
+///
+/// [`GitHubClient`]: crate::github::client::GitHubClient
+/// [`TokenFactory`]: crate::github::token::TokenFactory
+/// [`UserAuthenticator`]: crate::github::auth::UserAuthenticator
+pub(crate) fn http_client_or_default(
+    http_client: Option<&Client>,
+) -> Result<Client, reqwest::Error> {
+    if let Some(http_client) = http_client.or_else(|| DEFAULT_HTTP_CLIENT.get()) {
+        return Ok(http_client.clone());
+    }
+
+    let http_client = HttpClientConfig::default().build()?;
+
+    Ok(DEFAULT_HTTP_CLIENT.get_or_init(|| http_client).clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::header::{HeaderName, HeaderValue};
+    use reqwest::Proxy;
+
+    use super::HttpClientConfig;
+
+    #[test]
+    fn build_applies_config() {
+        let config = HttpClientConfig::default()
+            .connect_timeout(Some(Duration::from_secs(1)))
+            .timeout(None)
+            .user_agent("octocat/1.0")
+            .proxy(Proxy::all("http://localhost:3128").unwrap())
+            .default_header(
+                HeaderName::from_static("x-custom"),
+                HeaderValue::from_static("value"),
+            );
+
+        assert!(config.build().is_ok());
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<HttpClientConfig>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<HttpClientConfig>();
+    }
+}
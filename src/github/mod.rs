@@ -1,17 +1,15 @@
-use std::fmt::{Display, Formatter};
-
-use serde::{Deserialize, Serialize};
-
-use crate::name;
-
-pub use self::private_key::PrivateKey;
+pub use self::host::{GitHubHost, GitHubHostError};
+pub use self::http_client::HttpClientConfig;
+pub use self::private_key::{PrivateKey, PrivateKeyError};
 pub use self::webhook_secret::WebhookSecret;
 
+mod host;
+mod http_client;
 mod private_key;
 mod webhook_secret;
 
-name!(GitHubHost);
-
 pub mod app;
+pub mod auth;
 pub mod client;
+pub mod registry;
 pub mod token;
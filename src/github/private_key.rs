@@ -1,21 +1,111 @@
+use std::env::{var, VarError};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use jsonwebtoken::{crypto, Algorithm, EncodingKey};
 use secrecy::{ExposeSecret, SecretString};
 
 #[derive(Clone, Debug)]
 pub struct PrivateKey(SecretString);
 
+#[derive(Debug, thiserror::Error)]
+pub enum PrivateKeyError {
+    #[error("failed to read private key from {0}")]
+    File(PathBuf, #[source] std::io::Error),
+
+    #[error("failed to read private key from environment variable {0}")]
+    Environment(String, #[source] VarError),
+
+    #[error("failed to decode Base64 encoded private key")]
+    Base64(#[from] base64::DecodeError),
+
+    #[error("private key is not valid UTF-8")]
+    Encoding(#[from] std::string::FromUtf8Error),
+
+    #[error("private key must be an RSA private key in PKCS#1 or PKCS#8 PEM format")]
+    InvalidKey(#[source] jsonwebtoken::errors::Error),
+}
+
 impl PrivateKey {
+    /// Wraps the private key without validating it. Use [`PrivateKey::parse`] to catch malformed
+    /// keys before they are used.
     pub fn new(private_key: String) -> Self {
         Self(SecretString::new(private_key))
     }
 
+    /// Parses and validates a PEM encoded RSA private key.
+    ///
+    /// Escaped newlines (`\n`), which are common when keys are passed through environment
+    /// variables or JSON files, are replaced with real newlines before the key is validated.
+    pub fn parse(private_key: &str) -> Result<Self, PrivateKeyError> {
+        let private_key = private_key.trim().replace("\\n", "\n");
+
+        let encoding_key = EncodingKey::from_rsa_pem(private_key.as_bytes())
+            .map_err(PrivateKeyError::InvalidKey)?;
+
+        // Public keys are accepted by the parser above, so the only reliable way to check that
+        // the key can actually be used to authenticate the app is to sign something with it.
+        crypto::sign(b"github-parts", &encoding_key, Algorithm::RS256)
+            .map_err(PrivateKeyError::InvalidKey)?;
+
+        Ok(Self::new(private_key))
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, PrivateKeyError> {
+        let path = path.as_ref();
+
+        let private_key =
+            read_to_string(path).map_err(|error| PrivateKeyError::File(path.into(), error))?;
+
+        Self::parse(&private_key)
+    }
+
+    pub fn from_env(name: &str) -> Result<Self, PrivateKeyError> {
+        let private_key =
+            var(name).map_err(|error| PrivateKeyError::Environment(name.into(), error))?;
+
+        Self::parse(&private_key)
+    }
+
+    /// Reads a Base64 encoded private key from an environment variable.
+    pub fn from_base64_env(name: &str) -> Result<Self, PrivateKeyError> {
+        let encoded =
+            var(name).map_err(|error| PrivateKeyError::Environment(name.into(), error))?;
+
+        // Line breaks and surrounding whitespace are not part of the encoding, but often sneak
+        // into secrets when they are copied from a terminal.
+        let sanitized: String = encoded.split_whitespace().collect();
+        let private_key = String::from_utf8(base64::decode(sanitized)?)?;
+
+        Self::parse(&private_key)
+    }
+
     pub fn get(&self) -> &str {
         self.0.expose_secret()
     }
 }
 
+impl FromStr for PrivateKey {
+    type Err = PrivateKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::PrivateKey;
+    use std::env::set_var;
+
+    use super::{PrivateKey, PrivateKeyError};
+
+    const PKCS1: &str = include_str!("../../tests/fixtures/private-key.pem");
+    const PKCS8: &str = include_str!("../../tests/fixtures/private-key-pkcs8.pem");
+
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
 
     #[test]
     fn private_key() {
@@ -23,6 +113,94 @@ mod tests {
         assert_eq!("private_key", private_key.get());
     }
 
+    #[test]
+    fn parse_accepts_pkcs1() {
+        assert!(PrivateKey::parse(PKCS1).is_ok());
+    }
+
+    #[test]
+    fn parse_accepts_pkcs8() {
+        assert!(PrivateKey::parse(PKCS8).is_ok());
+    }
+
+    #[test]
+    fn parse_normalizes_escaped_newlines() {
+        let escaped = PKCS1.trim().replace('\n', "\\n");
+
+        let private_key = PrivateKey::parse(&escaped).unwrap();
+
+        assert_eq!(PKCS1.trim(), private_key.get());
+    }
+
+    #[test]
+    fn parse_rejects_public_key() {
+        let error =
+            PrivateKey::parse(include_str!("../../tests/fixtures/public-key.pem")).unwrap_err();
+
+        assert!(matches!(error, PrivateKeyError::InvalidKey(_)));
+    }
+
+    #[test]
+    fn parse_rejects_garbage() {
+        let error = PrivateKey::parse("private_key").unwrap_err();
+
+        assert!(matches!(error, PrivateKeyError::InvalidKey(_)));
+    }
+
+    #[test]
+    fn from_file_reads_key() {
+        assert!(PrivateKey::from_file(fixture("private-key.pem")).is_ok());
+    }
+
+    #[test]
+    fn from_file_fails_for_missing_file() {
+        let error = PrivateKey::from_file(fixture("missing.pem")).unwrap_err();
+
+        assert!(matches!(error, PrivateKeyError::File(_, _)));
+    }
+
+    #[test]
+    fn from_env_reads_key() {
+        set_var("GITHUB_PARTS_TEST_PRIVATE_KEY", PKCS1.replace('\n', "\\n"));
+
+        assert!(PrivateKey::from_env("GITHUB_PARTS_TEST_PRIVATE_KEY").is_ok());
+    }
+
+    #[test]
+    fn from_env_fails_for_missing_variable() {
+        let error = PrivateKey::from_env("GITHUB_PARTS_TEST_MISSING_PRIVATE_KEY").unwrap_err();
+
+        assert!(matches!(error, PrivateKeyError::Environment(_, _)));
+    }
+
+    #[test]
+    fn from_base64_env_reads_key() {
+        set_var(
+            "GITHUB_PARTS_TEST_BASE64_PRIVATE_KEY",
+            base64::encode(PKCS8),
+        );
+
+        assert!(PrivateKey::from_base64_env("GITHUB_PARTS_TEST_BASE64_PRIVATE_KEY").is_ok());
+    }
+
+    #[test]
+    fn from_base64_env_fails_for_invalid_encoding() {
+        set_var(
+            "GITHUB_PARTS_TEST_INVALID_BASE64_PRIVATE_KEY",
+            "not base64!",
+        );
+
+        let error = PrivateKey::from_base64_env("GITHUB_PARTS_TEST_INVALID_BASE64_PRIVATE_KEY")
+            .unwrap_err();
+
+        assert!(matches!(error, PrivateKeyError::Base64(_)));
+    }
+
+    #[test]
+    fn trait_from_str() {
+        assert!(PKCS1.parse::<PrivateKey>().is_ok());
+    }
+
     #[test]
     fn trait_send() {
         fn assert_send<T: Send>() {}
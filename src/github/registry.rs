@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use reqwest::Method;
+
+use crate::account::Login;
+use crate::github::app::AppId;
+use crate::github::client::{GitHubAppClient, GitHubClient, GitHubClientError};
+use crate::github::token::TokenFactory;
+use crate::github::{GitHubHost, PrivateKey};
+use crate::installation::{AppInstallation, InstallationId};
+use crate::repository::RepositoryName;
+
+/// Discovers the installations of a GitHub App and hands out clients for them.
+///
+/// Clients are created once per installation and share the token factory of the registry, so
+/// that installation tokens are cached across all of them.
+#[derive(Clone, Debug)]
+pub struct InstallationRegistry {
+    github_host: GitHubHost,
+    token_factory: TokenFactory,
+    app_client: GitHubAppClient,
+    clients: Arc<Mutex<HashMap<InstallationId, GitHubClient>>>,
+}
+
+impl InstallationRegistry {
+    #[tracing::instrument]
+    pub fn new(github_host: GitHubHost, app_id: AppId, private_key: PrivateKey) -> Self {
+        let token_factory = TokenFactory::new(github_host.clone(), app_id, private_key);
+
+        Self::with_token_factory(github_host, token_factory)
+    }
+
+    #[tracing::instrument]
+    pub fn with_token_factory(github_host: GitHubHost, token_factory: TokenFactory) -> Self {
+        let app_client =
+            GitHubAppClient::with_token_factory(github_host.clone(), token_factory.clone());
+
+        Self {
+            github_host,
+            token_factory,
+            app_client,
+            clients: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn app_client(&self) -> &GitHubAppClient {
+        &self.app_client
+    }
+
+    pub fn token_factory(&self) -> &TokenFactory {
+        &self.token_factory
+    }
+
+    /// Returns the client for the installation, and creates it if it does not exist yet.
+    #[tracing::instrument]
+    pub fn client(&self, installation_id: InstallationId) -> GitHubClient {
+        self.clients
+            .lock()
+            .entry(installation_id)
+            .or_insert_with(|| {
+                GitHubClient::with_token_factory(
+                    self.github_host.clone(),
+                    self.token_factory.clone(),
+                    installation_id,
+                )
+            })
+            .clone()
+    }
+
+    /// Lists every installation of the app.
+    #[tracing::instrument]
+    pub async fn installations(&self) -> Result<Vec<AppInstallation>, GitHubClientError> {
+        self.app_client
+            .paginate(Method::GET, "/app/installations", "installations")
+            .await
+    }
+
+    #[tracing::instrument]
+    pub async fn repository_installation(
+        &self,
+        owner: &Login,
+        repository: &RepositoryName,
+    ) -> Result<AppInstallation, GitHubClientError> {
+        let endpoint = format!("/repos/{}/{}/installation", owner.get(), repository.get());
+
+        self.app_client.get(&endpoint).await
+    }
+
+    #[tracing::instrument]
+    pub async fn organization_installation(
+        &self,
+        organization: &Login,
+    ) -> Result<AppInstallation, GitHubClientError> {
+        let endpoint = format!("/orgs/{}/installation", organization.get());
+
+        self.app_client.get(&endpoint).await
+    }
+
+    #[tracing::instrument]
+    pub async fn user_installation(
+        &self,
+        user: &Login,
+    ) -> Result<AppInstallation, GitHubClientError> {
+        let endpoint = format!("/users/{}/installation", user.get());
+
+        self.app_client.get(&endpoint).await
+    }
+
+    /// Returns the client for the installation that has access to the repository.
+    #[tracing::instrument]
+    pub async fn repository_client(
+        &self,
+        owner: &Login,
+        repository: &RepositoryName,
+    ) -> Result<GitHubClient, GitHubClientError> {
+        let installation = self.repository_installation(owner, repository).await?;
+
+        Ok(self.client(installation.id()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::mock;
+
+    use crate::account::Login;
+    use crate::github::app::AppId;
+    use crate::github::{GitHubHost, PrivateKey};
+    use crate::installation::InstallationId;
+    use crate::repository::{Repository, RepositoryName};
+    use crate::testing::token::mock_installation_access_tokens;
+
+    use super::InstallationRegistry;
+
+    fn registry() -> InstallationRegistry {
+        InstallationRegistry::new(
+            GitHubHost::new(&mockito::server_url()).unwrap(),
+            AppId::new(1),
+            PrivateKey::new(include_str!("../../tests/fixtures/private-key.pem").into()),
+        )
+    }
+
+    #[tokio::test]
+    async fn installations_returns_all_installations() {
+        let _installations_mock = mock("GET", "/app/installations")
+            .with_status(200)
+            .with_body(
+                r#"
+                [
+                    {
+                        "id": 1,
+                        "account": { "login": "octocat", "id": 1, "type": "User" },
+                        "app_id": 1
+                    },
+                    {
+                        "id": 2,
+                        "account": { "login": "github", "id": 2, "type": "Organization" },
+                        "app_id": 1
+                    }
+                ]
+            "#,
+            )
+            .create();
+
+        let installations = registry().installations().await.unwrap();
+
+        assert_eq!(2, installations.len());
+        assert_eq!(
+            "github",
+            installations[1].account().as_ref().unwrap().login().get()
+        );
+    }
+
+    #[tokio::test]
+    async fn repository_client_uses_installation_of_repository() {
+        let _installation_mock = mock("GET", "/repos/octocat/Hello-World/installation")
+            .with_status(200)
+            .with_body(
+                r#"
+                {
+                    "id": 1,
+                    "account": { "login": "octocat", "id": 1, "type": "User" },
+                    "app_id": 1
+                }
+            "#,
+            )
+            .create();
+        let _token_mock = mock_installation_access_tokens();
+        let _repository_mock = mock("GET", "/repos/octocat/Hello-World")
+            .with_status(200)
+            .with_body(
+                r#"
+                {
+                    "id": 1296269,
+                    "name": "Hello-World",
+                    "description": "This your first repo!",
+                    "owner": {
+                        "login": "octocat",
+                        "id": 1,
+                        "type": "User"
+                    },
+                    "visibility": "public"
+                }
+            "#,
+            )
+            .create();
+
+        let client = registry()
+            .repository_client(&Login::new("octocat"), &RepositoryName::new("Hello-World"))
+            .await
+            .unwrap();
+
+        let repository: Repository = client.get("/repos/octocat/Hello-World").await.unwrap();
+
+        assert_eq!(1296269, repository.id().get());
+    }
+
+    #[tokio::test]
+    async fn organization_installation_returns_installation() {
+        let _installation_mock = mock("GET", "/orgs/github/installation")
+            .with_status(200)
+            .with_body(
+                r#"
+                {
+                    "id": 2,
+                    "account": { "login": "github", "id": 2, "type": "Organization" },
+                    "app_id": 1
+                }
+            "#,
+            )
+            .create();
+
+        let installation = registry()
+            .organization_installation(&Login::new("github"))
+            .await
+            .unwrap();
+
+        assert_eq!(InstallationId::new(2), installation.id());
+    }
+
+    #[test]
+    fn client_is_cached_per_installation() {
+        let registry = registry();
+
+        registry.client(InstallationId::new(1));
+        registry.client(InstallationId::new(1));
+        registry.client(InstallationId::new(2));
+
+        assert_eq!(2, registry.clients.lock().len());
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<InstallationRegistry>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<InstallationRegistry>();
+    }
+}
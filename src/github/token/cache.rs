@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::github::token::{DropReason, Installation, Token};
+
+pub const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+#[derive(Clone, Debug)]
+pub(super) struct TokenCache {
+    capacity: usize,
+    tokens: HashMap<Installation, Token<Installation>>,
+}
+
+impl TokenCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            tokens: HashMap::new(),
+        }
+    }
+
+    /// Returns the token for the scope if it is still valid at `refresh_at`.
+    pub fn get(
+        &self,
+        scope: &Installation,
+        refresh_at: DateTime<Utc>,
+    ) -> Option<Token<Installation>> {
+        self.tokens
+            .get(scope)
+            .filter(|token| token.expires_at > refresh_at)
+            .cloned()
+    }
+
+    pub fn contains(&self, scope: &Installation) -> bool {
+        self.tokens.contains_key(scope)
+    }
+
+    /// Caches the token under its scope and returns the tokens that were dropped to make room.
+    pub fn insert(
+        &mut self,
+        token: Token<Installation>,
+        now: DateTime<Utc>,
+    ) -> Vec<(Installation, DropReason)> {
+        let dropped =
+            if !self.tokens.contains_key(&token.scope) && self.tokens.len() >= self.capacity {
+                self.evict(now)
+            } else {
+                Vec::new()
+            };
+
+        self.tokens.insert(token.scope.clone(), token);
+
+        dropped
+    }
+
+    /// Removes the token from the cache, unless it has already been replaced by a newer one.
+    pub fn remove(&mut self, scope: &Installation, token: &str) -> bool {
+        let is_cached = self
+            .tokens
+            .get(scope)
+            .map(|cached| cached.get() == token)
+            .unwrap_or(false);
+
+        if is_cached {
+            self.tokens.remove(scope);
+        }
+
+        is_cached
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    fn evict(&mut self, now: DateTime<Utc>) -> Vec<(Installation, DropReason)> {
+        let expired: Vec<Installation> = self
+            .tokens
+            .iter()
+            .filter(|(_, token)| token.expires_at <= now)
+            .map(|(scope, _)| scope.clone())
+            .collect();
+
+        let mut dropped: Vec<(Installation, DropReason)> = expired
+            .into_iter()
+            .filter_map(|scope| self.tokens.remove_entry(&scope))
+            .map(|(scope, _)| (scope, DropReason::Expired))
+            .collect();
+
+        if self.tokens.len() < self.capacity {
+            return dropped;
+        }
+
+        // When every cached token is still valid, drop the one that expires first since it
+        // would have to be refreshed soonest anyway.
+        let oldest = self
+            .tokens
+            .iter()
+            .min_by_key(|(_, token)| token.expires_at)
+            .map(|(scope, _)| scope.clone());
+
+        if let Some((scope, _)) = oldest.and_then(|scope| self.tokens.remove_entry(&scope)) {
+            dropped.push((scope, DropReason::Evicted));
+        }
+
+        dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Add;
+
+    use chrono::{Duration, Utc};
+    use secrecy::SecretString;
+
+    use crate::github::token::{
+        DropReason, Installation, Permission, PermissionLevel, Token, TokenRequest,
+    };
+    use crate::installation::InstallationId;
+
+    use super::TokenCache;
+
+    fn scope(installation_id: u64) -> Installation {
+        Installation::new(
+            InstallationId::new(installation_id),
+            TokenRequest::default(),
+        )
+    }
+
+    fn token(scope: Installation, value: &str, expires_in: Duration) -> Token<Installation> {
+        Token {
+            scope,
+            token: SecretString::new(value.into()),
+            expires_at: Utc::now().add(expires_in),
+        }
+    }
+
+    #[test]
+    fn get_returns_token_for_installation() {
+        let mut cache = TokenCache::new(10);
+        let now = Utc::now();
+
+        cache.insert(token(scope(1), "one", Duration::hours(1)), now);
+        cache.insert(token(scope(2), "two", Duration::hours(1)), now);
+
+        assert_eq!("one", cache.get(&scope(1), now).unwrap().get());
+        assert_eq!("two", cache.get(&scope(2), now).unwrap().get());
+    }
+
+    #[test]
+    fn get_ignores_token_that_must_be_refreshed() {
+        let mut cache = TokenCache::new(10);
+        let now = Utc::now();
+
+        cache.insert(token(scope(1), "one", Duration::minutes(1)), now);
+
+        assert!(cache
+            .get(&scope(1), now.add(Duration::minutes(2)))
+            .is_none());
+        assert!(cache.contains(&scope(1)));
+    }
+
+    #[test]
+    fn insert_evicts_expired_tokens_first() {
+        let mut cache = TokenCache::new(2);
+        let now = Utc::now();
+
+        cache.insert(token(scope(1), "one", Duration::hours(1)), now);
+        cache.insert(token(scope(2), "two", -Duration::hours(1)), now);
+        let dropped = cache.insert(token(scope(3), "three", Duration::hours(1)), now);
+
+        assert_eq!(vec![(scope(2), DropReason::Expired)], dropped);
+        assert_eq!(2, cache.len());
+        assert!(cache.get(&scope(1), now).is_some());
+        assert!(cache.get(&scope(3), now).is_some());
+    }
+
+    #[test]
+    fn insert_evicts_token_that_expires_first_when_full() {
+        let mut cache = TokenCache::new(2);
+        let now = Utc::now();
+
+        cache.insert(token(scope(1), "one", Duration::hours(2)), now);
+        cache.insert(token(scope(2), "two", Duration::hours(1)), now);
+        let dropped = cache.insert(token(scope(3), "three", Duration::hours(1)), now);
+
+        assert_eq!(vec![(scope(2), DropReason::Evicted)], dropped);
+        assert_eq!(2, cache.len());
+        assert!(cache.get(&scope(1), now).is_some());
+        assert!(cache.get(&scope(2), now).is_none());
+    }
+
+    #[test]
+    fn insert_replaces_token_without_eviction() {
+        let mut cache = TokenCache::new(1);
+        let now = Utc::now();
+
+        cache.insert(token(scope(1), "old", Duration::hours(1)), now);
+        let dropped = cache.insert(token(scope(1), "new", Duration::hours(1)), now);
+
+        assert!(dropped.is_empty());
+        assert_eq!(1, cache.len());
+        assert_eq!("new", cache.get(&scope(1), now).unwrap().get());
+    }
+
+    #[test]
+    fn get_separates_tokens_by_request() {
+        let mut cache = TokenCache::new(10);
+        let now = Utc::now();
+        let scoped = Installation::new(
+            InstallationId::new(1),
+            TokenRequest::new().permission(Permission::new("checks"), PermissionLevel::Write),
+        );
+
+        cache.insert(token(scope(1), "full", Duration::hours(1)), now);
+        cache.insert(token(scoped.clone(), "scoped", Duration::hours(1)), now);
+
+        assert_eq!("full", cache.get(&scope(1), now).unwrap().get());
+        assert_eq!("scoped", cache.get(&scoped, now).unwrap().get());
+    }
+
+    #[test]
+    fn remove_drops_token() {
+        let mut cache = TokenCache::new(10);
+        let now = Utc::now();
+        let token = token(scope(1), "one", Duration::hours(1));
+
+        cache.insert(token.clone(), now);
+
+        assert!(cache.remove(&scope(1), token.get()));
+        assert!(!cache.contains(&scope(1)));
+    }
+
+    #[test]
+    fn remove_keeps_newer_token() {
+        let mut cache = TokenCache::new(10);
+        let now = Utc::now();
+        let old = token(scope(1), "old", Duration::hours(1));
+
+        cache.insert(old.clone(), now);
+        cache.insert(token(scope(1), "new", Duration::hours(1)), now);
+
+        assert!(!cache.remove(&scope(1), old.get()));
+        assert_eq!("new", cache.get(&scope(1), now).unwrap().get());
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt::{Debug, Display, Formatter};
+
+use chrono::{DateTime, Utc};
+
+use crate::github::token::Installation;
+
+/// Receives lifecycle events for the installation tokens that a `TokenFactory` manages.
+///
+/// Hooks are called synchronously from the factory and should return quickly, for example by
+/// recording the event or forwarding it to a channel. Events never contain the token itself.
+pub trait TokenHook: Debug + Send + Sync {
+    fn on_event(&self, event: &TokenEvent);
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum TokenEvent {
+    /// A token was created for a scope that had no token yet.
+    Minted {
+        scope: Installation,
+        expires_at: DateTime<Utc>,
+    },
+
+    /// A token was created to replace a cached token, e.g. one that was about to expire.
+    Refreshed {
+        scope: Installation,
+        expires_at: DateTime<Utc>,
+    },
+
+    /// A token was removed from the cache.
+    Dropped {
+        scope: Installation,
+        reason: DropReason,
+    },
+}
+
+impl TokenEvent {
+    pub fn scope(&self) -> &Installation {
+        match self {
+            TokenEvent::Minted { scope, .. } => scope,
+            TokenEvent::Refreshed { scope, .. } => scope,
+            TokenEvent::Dropped { scope, .. } => scope,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum DropReason {
+    /// The token expired and was evicted from the cache.
+    Expired,
+
+    /// The cache was full and the token was evicted to make room for another one.
+    Evicted,
+
+    /// The token was revoked.
+    Revoked,
+
+    /// GitHub rejected the token, e.g. because it was revoked by another process.
+    Rejected,
+}
+
+impl Display for DropReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            DropReason::Expired => "expired",
+            DropReason::Evicted => "evicted",
+            DropReason::Revoked => "revoked",
+            DropReason::Rejected => "rejected",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DropReason, TokenEvent};
+
+    #[test]
+    fn trait_display() {
+        assert_eq!("expired", DropReason::Expired.to_string());
+        assert_eq!("evicted", DropReason::Evicted.to_string());
+        assert_eq!("revoked", DropReason::Revoked.to_string());
+        assert_eq!("rejected", DropReason::Rejected.to_string());
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<TokenEvent>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<TokenEvent>();
+    }
+}
@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::sync::Arc;
+
+use futures::future::{BoxFuture, Shared};
+use futures::FutureExt;
+
+use crate::error::Error;
+use crate::github::token::{Installation, Token};
+
+pub(super) type Refresh = Shared<BoxFuture<'static, Result<Token<Installation>, Arc<Error>>>>;
+
+/// Token refreshes that are currently in progress, so that concurrent callers can wait for the
+/// same request instead of each sending their own.
+#[derive(Default)]
+pub(super) struct InFlight {
+    refreshes: HashMap<Installation, Refresh>,
+}
+
+impl InFlight {
+    pub fn join_or_start<F>(&mut self, key: Installation, refresh: F) -> Refresh
+    where
+        F: Future<Output = Result<Token<Installation>, Arc<Error>>> + Send + 'static,
+    {
+        self.refreshes
+            .entry(key)
+            .or_insert_with(|| refresh.boxed().shared())
+            .clone()
+    }
+
+    pub fn finish(&mut self, key: &Installation) {
+        self.refreshes.remove(key);
+    }
+}
+
+impl Debug for InFlight {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.refreshes.keys()).finish()
+    }
+}
@@ -0,0 +1,1183 @@
+use std::collections::HashMap;
+use std::ops::Sub;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::{Mutex, RwLock};
+use reqwest::{Client, StatusCode};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::github::app::AppId;
+use crate::github::client::{ApiVersion, MediaType, SharedLimits, WeakLimits};
+use crate::github::http_client::http_client_or_default;
+use crate::github::{GitHubHost, PrivateKey};
+use crate::installation::InstallationId;
+
+use self::cache::{TokenCache, DEFAULT_CACHE_CAPACITY};
+use self::in_flight::InFlight;
+
+pub use self::hook::{DropReason, TokenEvent, TokenHook};
+pub use self::request::{Permission, PermissionLevel, TokenRequest};
+pub use self::signer::{JwtSigner, PrivateKeySigner};
+
+mod cache;
+mod hook;
+mod in_flight;
+mod request;
+mod signer;
+
+const DEFAULT_REFRESH_SKEW_SECONDS: i64 = 60;
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct App;
+
+/// The scope of an installation access token: the installation it was created for, and the
+/// restrictions that were requested for it.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Installation {
+    installation_id: InstallationId,
+    request: TokenRequest,
+}
+
+impl Installation {
+    pub fn new(installation_id: InstallationId, request: TokenRequest) -> Self {
+        Self {
+            installation_id,
+            request,
+        }
+    }
+
+    pub fn installation_id(&self) -> InstallationId {
+        self.installation_id
+    }
+
+    pub fn request(&self) -> &TokenRequest {
+        &self.request
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Token<Scope> {
+    scope: Scope,
+    token: SecretString,
+    expires_at: DateTime<Utc>,
+}
+
+impl<Scope> Token<Scope> {
+    pub fn get(&self) -> &str {
+        self.token.expose_secret()
+    }
+
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+
+    /// Returns how long the token remains valid, or a zero duration if it has already expired.
+    pub fn expires_in(&self) -> Duration {
+        let remaining = self.expires_at - Utc::now();
+
+        if remaining < Duration::zero() {
+            Duration::zero()
+        } else {
+            remaining
+        }
+    }
+}
+
+/// The signers of the GitHub App, and a generation that changes whenever they are rotated.
+#[derive(Debug)]
+struct Signers {
+    generation: u64,
+    signers: Vec<Arc<dyn JwtSigner>>,
+}
+
+/// The cached token of the GitHub App, and the signer that creates it.
+#[derive(Clone, Debug)]
+struct AppToken {
+    token: Token<App>,
+    signer: usize,
+    generation: u64,
+}
+
+impl AppToken {
+    fn expired(signer: usize, generation: u64) -> Self {
+        Self {
+            token: expired_app_token(),
+            signer,
+            generation,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TokenFactory {
+    github_host: GitHubHost,
+    app_id: AppId,
+    signers: Arc<RwLock<Signers>>,
+    refresh_skew: Duration,
+    app_token: Arc<Mutex<AppToken>>,
+    installation_tokens: Arc<Mutex<TokenCache>>,
+    in_flight: Arc<Mutex<InFlight>>,
+    shared_limits: Arc<Mutex<HashMap<InstallationId, WeakLimits>>>,
+    hooks: Vec<Arc<dyn TokenHook>>,
+    http_client: Option<Client>,
+    api_version: ApiVersion,
+}
+
+impl TokenFactory {
+    #[tracing::instrument]
+    pub fn new(github_host: GitHubHost, app_id: AppId, private_key: PrivateKey) -> Self {
+        Self::from_signer(
+            github_host,
+            app_id,
+            Arc::new(PrivateKeySigner::new(private_key)),
+        )
+    }
+
+    /// Creates a factory that signs the tokens of the GitHub App with a custom signer, for
+    /// example one that keeps the private key in an HSM.
+    #[tracing::instrument]
+    pub fn from_signer(github_host: GitHubHost, app_id: AppId, signer: Arc<dyn JwtSigner>) -> Self {
+        Self::from_signers(github_host, app_id, vec![signer])
+    }
+
+    /// Creates a factory for a GitHub App that has several active private keys.
+    ///
+    /// The first key is the primary key and signs every token. If GitHub rejects a token, the
+    /// factory switches to the next key in the list.
+    #[tracing::instrument]
+    pub fn from_private_keys(
+        github_host: GitHubHost,
+        app_id: AppId,
+        private_keys: Vec<PrivateKey>,
+    ) -> Self {
+        Self::from_signers(github_host, app_id, private_key_signers(private_keys))
+    }
+
+    /// Creates a factory with an ordered list of signers, starting with the primary signer.
+    #[tracing::instrument]
+    pub fn from_signers(
+        github_host: GitHubHost,
+        app_id: AppId,
+        signers: Vec<Arc<dyn JwtSigner>>,
+    ) -> Self {
+        Self {
+            github_host,
+            app_id,
+            signers: Arc::new(RwLock::new(Signers {
+                generation: 0,
+                signers,
+            })),
+            refresh_skew: Duration::seconds(DEFAULT_REFRESH_SKEW_SECONDS),
+            app_token: Arc::new(Mutex::new(AppToken::expired(0, 0))),
+            installation_tokens: Arc::new(Mutex::new(TokenCache::new(DEFAULT_CACHE_CAPACITY))),
+            in_flight: Arc::new(Mutex::new(InFlight::default())),
+            shared_limits: Arc::new(Mutex::new(HashMap::new())),
+            hooks: Vec::new(),
+            http_client: None,
+            api_version: ApiVersion::default(),
+        }
+    }
+
+    /// Refreshes tokens this long before they actually expire.
+    ///
+    /// The skew accounts for clock drift and for the time a request takes to reach GitHub, so
+    /// that a token does not expire while it is in flight.
+    pub fn with_refresh_skew(mut self, refresh_skew: Duration) -> Self {
+        self.refresh_skew = refresh_skew;
+        self
+    }
+
+    /// Limits the number of installation tokens that are cached at the same time.
+    ///
+    /// Expired tokens are evicted first when the cache is full. If every token is still valid,
+    /// the token that expires next is dropped to make room.
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.installation_tokens = Arc::new(Mutex::new(TokenCache::new(capacity)));
+        self
+    }
+
+    /// Registers a hook that is notified when installation tokens are minted, refreshed, or
+    /// dropped.
+    pub fn with_hook(mut self, hook: Arc<dyn TokenHook>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    /// Sends requests with the given HTTP client, e.g. one built from an [`HttpClientConfig`].
+    ///
+    /// Clients that are created with this factory use the same HTTP client.
+    ///
+    /// [`HttpClientConfig`]: crate::github::HttpClientConfig
+    pub fn with_http_client(mut self, http_client: Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    /// Sends the API version in the `X-GitHub-Api-Version` header of the requests that create
+    /// and revoke tokens.
+    ///
+    /// Clients that are created with this factory use the same API version.
+    pub fn with_api_version(mut self, api_version: ApiVersion) -> Self {
+        self.api_version = api_version;
+        self
+    }
+
+    pub fn api_version(&self) -> &ApiVersion {
+        &self.api_version
+    }
+
+    pub fn app_id(&self) -> AppId {
+        self.app_id
+    }
+
+    /// Returns the HTTP client that was set with [`with_http_client`](Self::with_http_client),
+    /// or `None` if the factory uses the default client.
+    pub fn http_client(&self) -> Option<&Client> {
+        self.http_client.as_ref()
+    }
+
+    /// Returns the limits that every client of the installation shares.
+    ///
+    /// The factory only holds weak references, so the limits of an installation are released
+    /// once none of its clients is alive anymore.
+    pub(crate) fn shared_limits(&self, installation_id: InstallationId) -> SharedLimits {
+        let mut shared_limits = self.shared_limits.lock();
+
+        if let Some(limits) = shared_limits
+            .get(&installation_id)
+            .and_then(WeakLimits::upgrade)
+        {
+            return limits;
+        }
+
+        shared_limits.retain(|_, limits| !limits.is_released());
+
+        let limits = SharedLimits::default();
+        shared_limits.insert(installation_id, limits.downgrade());
+
+        limits
+    }
+
+    /// Replaces the private keys of the GitHub App, for example after a key rotation.
+    ///
+    /// Every clone of the factory, and thus every client that shares it, uses the new keys from
+    /// now on. Installation tokens that have already been created remain valid.
+    #[tracing::instrument]
+    pub fn rotate_private_keys(&self, private_keys: Vec<PrivateKey>) {
+        self.rotate_signers(private_key_signers(private_keys));
+    }
+
+    /// Replaces the signers of the GitHub App, starting with the new primary signer.
+    #[tracing::instrument]
+    pub fn rotate_signers(&self, signers: Vec<Arc<dyn JwtSigner>>) {
+        let mut current = self.signers.write();
+        current.generation += 1;
+        current.signers = signers;
+
+        *self.app_token.lock() = AppToken::expired(0, current.generation);
+    }
+
+    /// Returns a token that authenticates as the GitHub App.
+    ///
+    /// The token is signed with the primary signer, unless GitHub has rejected one of its tokens
+    /// before. Then the next signer is used until the signers are rotated.
+    #[tracing::instrument]
+    pub async fn app(&self) -> Result<Token<App>, Error> {
+        let refresh_at = Utc::now() + self.refresh_skew;
+
+        let (index, generation) = {
+            let app_token = self.app_token.lock();
+            if app_token.token.expires_at > refresh_at {
+                return Ok(app_token.token.clone());
+            }
+
+            (app_token.signer, app_token.generation)
+        };
+
+        let (signer, index, generation) = {
+            let signers = self.signers.read();
+
+            // The signers might have been rotated since the cached token was read.
+            let (index, generation) = if signers.generation == generation {
+                (index, generation)
+            } else {
+                (0, signers.generation)
+            };
+
+            (signers.signers.get(index).cloned(), index, generation)
+        };
+
+        let signer = signer.ok_or_else(|| {
+            Error::Configuration(
+                anyhow!("no private key has been configured").into(),
+                "failed to sign GitHub App token".into(),
+            )
+        })?;
+
+        let token = self.generate_jwt(signer.as_ref()).await?;
+
+        {
+            // Do not overwrite a token from newer signers, or from the next signer after this one
+            // has been rejected while the token was signed.
+            let mut app_token = self.app_token.lock();
+            if app_token.generation == generation && app_token.signer == index {
+                app_token.token = token.clone();
+            }
+        }
+
+        Ok(token)
+    }
+
+    /// Reports that GitHub rejected a token of the GitHub App, e.g. because its private key has
+    /// been revoked, and switches to the next signer.
+    ///
+    /// Returns `true` if a new token from [`app`](Self::app) might be accepted, and `false` if
+    /// every signer has been tried.
+    #[tracing::instrument(skip(token))]
+    pub fn reject_app_token(&self, token: &str) -> bool {
+        let signers = self.signers.read();
+        let mut app_token = self.app_token.lock();
+
+        // The token has already been replaced, e.g. by another request that was rejected too, or
+        // because the signers were rotated.
+        if app_token.generation != signers.generation || app_token.token.get() != token {
+            return true;
+        }
+
+        let next = app_token.signer + 1;
+        if next >= signers.signers.len() {
+            return false;
+        }
+
+        tracing::warn!("GitHub rejected the app token, switching to the next key");
+
+        // Keep using the key that GitHub accepts until the signers are rotated, instead of
+        // trying the rejected key again for every request.
+        *app_token = AppToken::expired(next, signers.generation);
+
+        true
+    }
+
+    /// Returns an access token with the full access of the installation.
+    ///
+    /// Concurrent calls for the same installation share a single request to GitHub. If that
+    /// request fails, every caller receives the error and the next call tries again.
+    #[tracing::instrument]
+    pub async fn installation(
+        &self,
+        installation_id: InstallationId,
+    ) -> Result<Token<Installation>, Error> {
+        self.scoped_installation(installation_id, &TokenRequest::default())
+            .await
+    }
+
+    /// Returns an access token that is limited to the repositories and permissions in the
+    /// request.
+    ///
+    /// Scoped tokens are cached separately from each other and from full-access tokens.
+    #[tracing::instrument]
+    pub async fn scoped_installation(
+        &self,
+        installation_id: InstallationId,
+        request: &TokenRequest,
+    ) -> Result<Token<Installation>, Error> {
+        let key = Installation::new(installation_id, request.clone());
+
+        if let Some(token) = self.cached_installation_token(&key) {
+            return Ok(token);
+        }
+
+        let refresh = {
+            let mut in_flight = self.in_flight.lock();
+
+            // Another task might have finished refreshing the token while we waited for the lock.
+            if let Some(token) = self.cached_installation_token(&key) {
+                return Ok(token);
+            }
+
+            let factory = self.clone();
+            in_flight.join_or_start(key.clone(), async move {
+                let result = factory.request_installation_token(&key).await;
+                factory.in_flight.lock().finish(&key);
+
+                result.map_err(Arc::new)
+            })
+        };
+
+        Ok(refresh.await?)
+    }
+
+    fn cached_installation_token(&self, key: &Installation) -> Option<Token<Installation>> {
+        self.installation_tokens
+            .lock()
+            .get(key, Utc::now() + self.refresh_skew)
+    }
+
+    #[tracing::instrument]
+    async fn request_installation_token(
+        &self,
+        key: &Installation,
+    ) -> Result<Token<Installation>, Error> {
+        let url = self.github_host.rest_url(&format!(
+            "/app/installations/{}/access_tokens",
+            key.installation_id
+        ));
+
+        let http_client = http_client_or_default(self.http_client.as_ref())?;
+
+        let response = loop {
+            let app_token = self.app().await?;
+
+            let mut request = http_client
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", app_token.get()))
+                .header("Accept", MediaType::Json.accept())
+                .header("X-GitHub-Api-Version", self.api_version.get());
+
+            if !key.request.is_full_access() {
+                request = request.json(&key.request);
+            }
+
+            let response = request.send().await?;
+
+            if response.status() == StatusCode::UNAUTHORIZED
+                && self.reject_app_token(app_token.get())
+            {
+                continue;
+            }
+
+            break response;
+        };
+
+        let response = response.error_for_status()?;
+
+        let access_token_response = response.json::<AccessTokensResponse>().await?;
+        let token = Token {
+            scope: key.clone(),
+            token: SecretString::new(access_token_response.token),
+            expires_at: access_token_response.expires_at,
+        };
+
+        let (refreshed, dropped) = {
+            let mut installation_tokens = self.installation_tokens.lock();
+
+            // Expired tokens stay in the cache until they are evicted or replaced, so a cached
+            // token for the scope means that this token replaces it.
+            let refreshed = installation_tokens.contains(key);
+            let dropped = installation_tokens.insert(token.clone(), Utc::now());
+
+            (refreshed, dropped)
+        };
+
+        for (scope, reason) in dropped {
+            self.emit(TokenEvent::Dropped { scope, reason });
+        }
+
+        let scope = key.clone();
+        let expires_at = token.expires_at;
+        self.emit(if refreshed {
+            TokenEvent::Refreshed { scope, expires_at }
+        } else {
+            TokenEvent::Minted { scope, expires_at }
+        });
+
+        Ok(token)
+    }
+
+    /// Revokes the installation token so that it can no longer be used, and removes it from the
+    /// cache.
+    ///
+    /// The next call for the same scope creates a new token.
+    #[tracing::instrument]
+    pub async fn revoke(&self, token: &Token<Installation>) -> Result<(), Error> {
+        let url = self.github_host.rest_url("/installation/token");
+
+        http_client_or_default(self.http_client.as_ref())?
+            .delete(url)
+            .header("Authorization", format!("Bearer {}", token.get()))
+            .header("Accept", MediaType::Json.accept())
+            .header("X-GitHub-Api-Version", self.api_version.get())
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let removed = self
+            .installation_tokens
+            .lock()
+            .remove(&token.scope, token.get());
+
+        // Only report a drop if the token was actually cached, and not already replaced by a
+        // newer token for the same scope.
+        if removed {
+            self.emit(TokenEvent::Dropped {
+                scope: token.scope.clone(),
+                reason: DropReason::Revoked,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Reports that GitHub rejected an installation token, e.g. because another process revoked
+    /// it, and removes it from the cache.
+    ///
+    /// The next call for the same scope creates a new token.
+    #[tracing::instrument(skip(token))]
+    pub fn reject_installation_token(
+        &self,
+        installation_id: InstallationId,
+        request: &TokenRequest,
+        token: &str,
+    ) {
+        let scope = Installation::new(installation_id, request.clone());
+        let removed = self.installation_tokens.lock().remove(&scope, token);
+
+        // The token might already have been replaced, e.g. by another request that was rejected
+        // too.
+        if removed {
+            tracing::warn!("GitHub rejected the installation token, requesting a new one");
+
+            self.emit(TokenEvent::Dropped {
+                scope,
+                reason: DropReason::Rejected,
+            });
+        }
+    }
+
+    fn emit(&self, event: TokenEvent) {
+        for hook in &self.hooks {
+            hook.on_event(&event);
+        }
+    }
+
+    #[tracing::instrument]
+    async fn generate_jwt(&self, signer: &dyn JwtSigner) -> Result<Token<App>, Error> {
+        let now = Utc::now();
+
+        let issued_at = now
+            .checked_sub_signed(Duration::seconds(60))
+            .context("failed to create timestamp for iat claimn in GitHub App token")?;
+
+        let expires_at = now
+            .checked_add_signed(Duration::minutes(10))
+            .context("failed to create timestamp for exp claim in GitHub App token")?;
+
+        let claims = Claims {
+            iat: issued_at.timestamp(),
+            iss: self.app_id.get().to_string(),
+            exp: expires_at.timestamp(),
+        };
+
+        let jwt = signer::encode(signer, &claims).await?;
+
+        Ok(Token {
+            scope: App,
+            token: SecretString::new(jwt),
+            expires_at,
+        })
+    }
+}
+
+fn expired_app_token() -> Token<App> {
+    Token {
+        scope: App,
+        token: SecretString::new("app_token".into()),
+        expires_at: Utc::now().sub(Duration::days(1)),
+    }
+}
+
+fn private_key_signers(private_keys: Vec<PrivateKey>) -> Vec<Arc<dyn JwtSigner>> {
+    private_keys
+        .into_iter()
+        .map(|private_key| Arc::new(PrivateKeySigner::new(private_key)) as Arc<dyn JwtSigner>)
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    iat: i64,
+    iss: String,
+    exp: i64,
+}
+
+#[derive(Deserialize, Serialize)]
+struct AccessTokensResponse {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::ops::{Add, Sub};
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use chrono::{Duration, Utc};
+    use futures::future::join_all;
+    use mockito::{mock, Matcher};
+    use parking_lot::{Mutex, RwLock};
+    use secrecy::SecretString;
+    use serde_json::json;
+
+    use crate::error::Error;
+    use crate::github::app::AppId;
+    use crate::github::client::ApiVersion;
+    use crate::github::{GitHubHost, PrivateKey};
+    use crate::installation::InstallationId;
+    use crate::repository::RepositoryName;
+    use crate::testing::token::{
+        installation_access_tokens, mock_installation_access_tokens, INSTALLATION_TOKEN,
+    };
+
+    use super::cache::TokenCache;
+    use super::in_flight::InFlight;
+    use super::{
+        App, AppToken, DropReason, Installation, JwtSigner, Permission, PermissionLevel,
+        PrivateKeySigner, Signers, Token, TokenEvent, TokenFactory, TokenHook, TokenRequest,
+    };
+
+    fn factory(
+        app_token: Option<Token<App>>,
+        installation_token: Option<Token<Installation>>,
+    ) -> TokenFactory {
+        let expiration = Utc::now().sub(Duration::days(1));
+
+        let app_token = match app_token {
+            Some(token) => token,
+            None => Token {
+                scope: App,
+                token: SecretString::new("app_token".into()),
+                expires_at: expiration,
+            },
+        };
+        let mut installation_tokens = TokenCache::new(10);
+        if let Some(token) = installation_token {
+            installation_tokens.insert(token, Utc::now());
+        }
+
+        TokenFactory {
+            github_host: GitHubHost::new(&mockito::server_url()).unwrap(),
+            app_id: AppId::new(1),
+            signers: Arc::new(RwLock::new(Signers {
+                generation: 0,
+                signers: vec![Arc::new(PrivateKeySigner::new(PrivateKey::new(
+                    include_str!("../../../tests/fixtures/private-key.pem").into(),
+                )))],
+            })),
+            refresh_skew: Duration::seconds(super::DEFAULT_REFRESH_SKEW_SECONDS),
+            app_token: Arc::new(Mutex::new(AppToken {
+                token: app_token,
+                signer: 0,
+                generation: 0,
+            })),
+            installation_tokens: Arc::new(Mutex::new(installation_tokens)),
+            in_flight: Arc::new(Mutex::new(InFlight::default())),
+            shared_limits: Arc::new(Mutex::new(HashMap::new())),
+            hooks: Vec::new(),
+            http_client: None,
+            api_version: ApiVersion::default(),
+        }
+    }
+
+    fn scope() -> Installation {
+        Installation::new(InstallationId::new(1), TokenRequest::default())
+    }
+
+    #[tokio::test]
+    async fn app_caches_token_while_it_is_not_expired() {
+        let token = Token {
+            scope: App,
+            token: SecretString::new("app".into()),
+            expires_at: Utc::now().add(Duration::minutes(10)),
+        };
+        let factory = factory(Some(token.clone()), None);
+
+        let new_token = factory.app().await.unwrap();
+
+        assert_eq!(new_token.get(), token.get());
+    }
+
+    #[tokio::test]
+    async fn app_generates_new_when_token_expired() {
+        let token = Token {
+            scope: App,
+            token: SecretString::new("app".into()),
+            expires_at: Utc::now().sub(Duration::minutes(10)),
+        };
+        let factory = factory(Some(token.clone()), None);
+
+        let new_token = factory.app().await.unwrap();
+
+        assert_ne!(new_token.get(), token.get());
+    }
+
+    #[tokio::test]
+    async fn app_records_expiration_of_jwt() {
+        let factory = factory(None, None);
+
+        let token = factory.app().await.unwrap();
+
+        assert!(token.expires_at() > Utc::now().add(Duration::minutes(9)));
+        assert!(token.expires_at() <= Utc::now().add(Duration::minutes(10)));
+    }
+
+    #[tokio::test]
+    async fn app_refreshes_token_within_refresh_skew() {
+        let token = Token {
+            scope: App,
+            token: SecretString::new("app".into()),
+            expires_at: Utc::now().add(Duration::minutes(1)),
+        };
+        let factory = factory(Some(token.clone()), None).with_refresh_skew(Duration::minutes(2));
+
+        let new_token = factory.app().await.unwrap();
+
+        assert_ne!(new_token.get(), token.get());
+    }
+
+    #[tokio::test]
+    async fn installation_caches_token_while_it_is_not_expired() {
+        let token = Token {
+            scope: scope(),
+            token: SecretString::new("installation".into()),
+            expires_at: Utc::now().add(Duration::minutes(10)),
+        };
+        let factory = factory(None, Some(token.clone()));
+
+        let new_token = factory.installation(InstallationId::new(1)).await.unwrap();
+
+        assert_eq!(new_token.get(), token.get());
+    }
+
+    #[tokio::test]
+    async fn installation_requests_new_when_token_expired() {
+        let _mock = mock_installation_access_tokens();
+
+        let app_token = Token {
+            scope: App,
+            token: SecretString::new("app".into()),
+            expires_at: Utc::now().sub(Duration::minutes(10)),
+        };
+        let installation_token = Token {
+            scope: scope(),
+            token: SecretString::new("installation".into()),
+            expires_at: Utc::now().add(Duration::minutes(10)),
+        };
+        let factory = factory(Some(app_token.clone()), Some(installation_token));
+
+        let new_token = factory.installation(InstallationId::new(1)).await.unwrap();
+
+        assert_ne!(new_token.get(), app_token.get());
+    }
+
+    #[tokio::test]
+    async fn installation_uses_expiration_from_github() {
+        let _mock = mock_installation_access_tokens();
+
+        let factory = factory(None, None);
+
+        let token = factory.installation(InstallationId::new(1)).await.unwrap();
+
+        assert_eq!("2099-01-01T00:00:00+00:00", token.expires_at().to_rfc3339());
+    }
+
+    #[tokio::test]
+    async fn installation_refreshes_token_within_refresh_skew() {
+        let _mock = mock_installation_access_tokens();
+
+        let token = Token {
+            scope: scope(),
+            token: SecretString::new("installation".into()),
+            expires_at: Utc::now().add(Duration::seconds(30)),
+        };
+        let factory = factory(None, Some(token.clone()));
+
+        let new_token = factory.installation(InstallationId::new(1)).await.unwrap();
+
+        assert_ne!(new_token.get(), token.get());
+    }
+
+    #[tokio::test]
+    async fn installation_shares_concurrent_refreshes() {
+        let mock = installation_access_tokens(1, INSTALLATION_TOKEN)
+            .expect(1)
+            .create();
+
+        let factory = factory(None, None);
+
+        let tokens = join_all((0..50).map(|_| factory.installation(InstallationId::new(1)))).await;
+
+        mock.assert();
+        assert!(tokens
+            .iter()
+            .all(|token| token.as_ref().unwrap().get() == INSTALLATION_TOKEN));
+    }
+
+    #[tokio::test]
+    async fn installation_returns_failed_refresh_to_every_caller() {
+        let failing_mock = mock("POST", "/app/installations/1/access_tokens")
+            .with_status(500)
+            .expect(1)
+            .create();
+
+        let factory = factory(None, None);
+
+        let results = join_all((0..10).map(|_| factory.installation(InstallationId::new(1)))).await;
+
+        failing_mock.assert();
+        assert!(results.iter().all(|result| result.is_err()));
+
+        drop(failing_mock);
+        let _mock = mock_installation_access_tokens();
+
+        let token = factory.installation(InstallationId::new(1)).await.unwrap();
+
+        assert_eq!(INSTALLATION_TOKEN, token.get());
+    }
+
+    #[tokio::test]
+    async fn scoped_installation_requests_restricted_token() {
+        let _mock = installation_access_tokens(1, "ghs_scoped")
+            .match_body(Matcher::Json(json!({
+                "repositories": ["github-parts"],
+                "permissions": { "checks": "write", "contents": "read" }
+            })))
+            .create();
+
+        let token = Token {
+            scope: scope(),
+            token: SecretString::new("installation".into()),
+            expires_at: Utc::now().add(Duration::minutes(10)),
+        };
+        let factory = factory(None, Some(token.clone()));
+        let request = TokenRequest::new()
+            .repository(RepositoryName::new("github-parts"))
+            .permission(Permission::new("checks"), PermissionLevel::Write)
+            .permission(Permission::new("contents"), PermissionLevel::Read);
+
+        let scoped = factory
+            .scoped_installation(InstallationId::new(1), &request)
+            .await
+            .unwrap();
+        let full = factory.installation(InstallationId::new(1)).await.unwrap();
+
+        assert_eq!("ghs_scoped", scoped.get());
+        assert_eq!(token.get(), full.get());
+    }
+
+    #[tokio::test]
+    async fn revoke_removes_token_from_cache() {
+        let revoke_mock = mock("DELETE", "/installation/token")
+            .match_header("authorization", "Bearer installation")
+            .with_status(204)
+            .create();
+        let _token_mock = mock_installation_access_tokens();
+
+        let token = Token {
+            scope: scope(),
+            token: SecretString::new("installation".into()),
+            expires_at: Utc::now().add(Duration::minutes(10)),
+        };
+        let factory = factory(None, Some(token.clone()));
+
+        factory.revoke(&token).await.unwrap();
+        let new_token = factory.installation(InstallationId::new(1)).await.unwrap();
+
+        revoke_mock.assert();
+        assert_ne!(token.get(), new_token.get());
+    }
+
+    #[tokio::test]
+    async fn revoke_keeps_token_in_cache_when_request_fails() {
+        let _mock = mock("DELETE", "/installation/token")
+            .with_status(401)
+            .create();
+
+        let token = Token {
+            scope: scope(),
+            token: SecretString::new("installation".into()),
+            expires_at: Utc::now().add(Duration::minutes(10)),
+        };
+        let factory = factory(None, Some(token.clone()));
+
+        assert!(factory.revoke(&token).await.is_err());
+        assert_eq!(1, factory.installation_tokens.lock().len());
+    }
+
+    #[tokio::test]
+    async fn hooks_receive_lifecycle_events() {
+        let _token_mock = mock_installation_access_tokens();
+        let _revoke_mock = mock("DELETE", "/installation/token")
+            .with_status(204)
+            .create();
+
+        let hook = Arc::new(RecordingHook::default());
+        let expiring_token = Token {
+            scope: scope(),
+            token: SecretString::new("installation".into()),
+            expires_at: Utc::now().add(Duration::seconds(10)),
+        };
+        let factory = factory(None, Some(expiring_token)).with_hook(hook.clone());
+
+        let token = factory.installation(InstallationId::new(1)).await.unwrap();
+        factory.revoke(&token).await.unwrap();
+        factory.installation(InstallationId::new(1)).await.unwrap();
+
+        let events = hook.events.lock();
+        assert!(matches!(events[0], TokenEvent::Refreshed { .. }));
+        assert!(matches!(
+            events[1],
+            TokenEvent::Dropped {
+                reason: DropReason::Revoked,
+                ..
+            }
+        ));
+        assert!(matches!(events[2], TokenEvent::Minted { .. }));
+        assert!(events.iter().all(|event| event.scope() == &scope()));
+    }
+
+    #[tokio::test]
+    async fn revoke_ignores_token_that_is_not_cached() {
+        let _revoke_mock = mock("DELETE", "/installation/token")
+            .with_status(204)
+            .create();
+
+        let hook = Arc::new(RecordingHook::default());
+        let cached = Token {
+            scope: scope(),
+            token: SecretString::new("cached".into()),
+            expires_at: Utc::now().add(Duration::minutes(10)),
+        };
+        let revoked = Token {
+            scope: scope(),
+            token: SecretString::new("revoked".into()),
+            expires_at: Utc::now().add(Duration::minutes(10)),
+        };
+        let factory = factory(None, Some(cached)).with_hook(hook.clone());
+
+        factory.revoke(&revoked).await.unwrap();
+
+        assert!(hook.events.lock().is_empty());
+        assert_eq!(1, factory.installation_tokens.lock().len());
+    }
+
+    #[test]
+    fn reject_installation_token_removes_token_from_cache() {
+        let hook = Arc::new(RecordingHook::default());
+        let token = Token {
+            scope: scope(),
+            token: SecretString::new("installation".into()),
+            expires_at: Utc::now().add(Duration::minutes(10)),
+        };
+        let factory = factory(None, Some(token.clone())).with_hook(hook.clone());
+
+        factory.reject_installation_token(
+            InstallationId::new(1),
+            &TokenRequest::default(),
+            token.get(),
+        );
+        factory.reject_installation_token(
+            InstallationId::new(1),
+            &TokenRequest::default(),
+            token.get(),
+        );
+
+        let events = hook.events.lock();
+        assert_eq!(1, events.len());
+        assert!(matches!(
+            events[0],
+            TokenEvent::Dropped {
+                reason: DropReason::Rejected,
+                ..
+            }
+        ));
+        assert_eq!(0, factory.installation_tokens.lock().len());
+    }
+
+    #[tokio::test]
+    async fn installation_sends_api_version_of_factory() {
+        let token_mock = installation_access_tokens(42, INSTALLATION_TOKEN)
+            .match_header("X-GitHub-Api-Version", "2026-03-10")
+            .expect(1)
+            .create();
+
+        let factory = factory(None, None).with_api_version(ApiVersion::new("2026-03-10"));
+
+        factory.installation(InstallationId::new(42)).await.unwrap();
+
+        token_mock.assert();
+    }
+
+    #[test]
+    fn shared_limits_of_idle_installation_are_released() {
+        let factory = factory(None, None);
+
+        let active = factory.shared_limits(InstallationId::new(1));
+        let idle = factory.shared_limits(InstallationId::new(2)).downgrade();
+
+        let _new = factory.shared_limits(InstallationId::new(3));
+
+        let shared_limits = factory.shared_limits.lock();
+        assert!(idle.is_released());
+        assert!(!shared_limits.contains_key(&InstallationId::new(2)));
+        assert!(shared_limits.contains_key(&InstallationId::new(1)));
+        assert!(!active.downgrade().is_released());
+    }
+
+    #[derive(Debug, Default)]
+    struct RecordingHook {
+        events: Mutex<Vec<TokenEvent>>,
+    }
+
+    impl TokenHook for RecordingHook {
+        fn on_event(&self, event: &TokenEvent) {
+            self.events.lock().push(event.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn app_signs_jwt_with_custom_signer() {
+        let factory = TokenFactory::from_signer(
+            GitHubHost::new(&mockito::server_url()).unwrap(),
+            AppId::new(1),
+            Arc::new(FixedSigner(b"signature")),
+        );
+
+        let token = factory.app().await.unwrap();
+
+        assert!(token.get().ends_with(".c2lnbmF0dXJl"));
+    }
+
+    #[tokio::test]
+    async fn installation_falls_back_to_next_key_when_token_is_rejected() {
+        let rejected_mock = mock("POST", "/app/installations/1/access_tokens")
+            .match_header("authorization", Matcher::Regex(r"\.cmV2b2tlZA$".into()))
+            .with_status(401)
+            .expect(1)
+            .create();
+        let _accepted_mock = mock_installation_access_tokens();
+
+        let factory = TokenFactory::from_signers(
+            GitHubHost::new(&mockito::server_url()).unwrap(),
+            AppId::new(1),
+            vec![
+                Arc::new(FixedSigner(b"revoked")),
+                Arc::new(FixedSigner(b"signature")),
+            ],
+        );
+
+        let token = factory.installation(InstallationId::new(1)).await.unwrap();
+        let app_token = factory.app().await.unwrap();
+
+        rejected_mock.assert();
+        assert_eq!(INSTALLATION_TOKEN, token.get());
+        assert!(app_token.get().ends_with(".c2lnbmF0dXJl"));
+    }
+
+    #[tokio::test]
+    async fn installation_fails_when_every_key_is_rejected() {
+        let mock = mock("POST", "/app/installations/5/access_tokens")
+            .with_status(401)
+            .expect(2)
+            .create();
+
+        let factory = TokenFactory::from_signers(
+            GitHubHost::new(&mockito::server_url()).unwrap(),
+            AppId::new(1),
+            vec![
+                Arc::new(FixedSigner(b"revoked")),
+                Arc::new(FixedSigner(b"signature")),
+            ],
+        );
+
+        assert!(factory.installation(InstallationId::new(5)).await.is_err());
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn rotate_signers_applies_to_every_clone() {
+        let factory = TokenFactory::from_signer(
+            GitHubHost::new(&mockito::server_url()).unwrap(),
+            AppId::new(1),
+            Arc::new(FixedSigner(b"revoked")),
+        );
+        let clone = factory.clone();
+
+        let old_token = clone.app().await.unwrap();
+        factory.rotate_signers(vec![Arc::new(FixedSigner(b"signature"))]);
+        let new_token = clone.app().await.unwrap();
+
+        assert!(old_token.get().ends_with(".cmV2b2tlZA"));
+        assert!(new_token.get().ends_with(".c2lnbmF0dXJl"));
+    }
+
+    #[tokio::test]
+    async fn app_fails_without_private_key() {
+        let factory = TokenFactory::from_private_keys(
+            GitHubHost::new(&mockito::server_url()).unwrap(),
+            AppId::new(1),
+            Vec::new(),
+        );
+
+        let error = factory.app().await.unwrap_err();
+
+        assert!(matches!(error, Error::Configuration(_, _)));
+    }
+
+    #[derive(Debug)]
+    struct FixedSigner(&'static [u8]);
+
+    #[async_trait]
+    impl JwtSigner for FixedSigner {
+        async fn sign(&self, _message: &[u8]) -> Result<Vec<u8>, Error> {
+            Ok(self.0.to_vec())
+        }
+    }
+
+    #[test]
+    fn expires_in_is_zero_for_expired_token() {
+        let token: Token<App> = Token {
+            scope: App,
+            token: SecretString::new("app".into()),
+            expires_at: Utc::now().sub(Duration::minutes(1)),
+        };
+
+        assert_eq!(Duration::zero(), token.expires_in());
+    }
+
+    #[tokio::test]
+    async fn installation_caches_tokens_per_installation() {
+        let _mock = installation_access_tokens(2, INSTALLATION_TOKEN).create();
+
+        let token = Token {
+            scope: scope(),
+            token: SecretString::new("installation".into()),
+            expires_at: Utc::now().add(Duration::minutes(10)),
+        };
+        let factory = factory(None, Some(token.clone()));
+
+        let first = factory.installation(InstallationId::new(1)).await.unwrap();
+        let second = factory.installation(InstallationId::new(2)).await.unwrap();
+
+        assert_eq!(token.get(), first.get());
+        assert_eq!(INSTALLATION_TOKEN, second.get());
+        assert_eq!(2, factory.installation_tokens.lock().len());
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Token<App>>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<Token<App>>();
+    }
+}
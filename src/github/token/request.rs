@@ -0,0 +1,132 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::name;
+use crate::repository::{RepositoryId, RepositoryName};
+
+name!(Permission);
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionLevel {
+    Read,
+    Write,
+    Admin,
+}
+
+impl Display for PermissionLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            PermissionLevel::Read => "read",
+            PermissionLevel::Write => "write",
+            PermissionLevel::Admin => "admin",
+        };
+
+        write!(f, "{}", string)
+    }
+}
+
+/// Restricts the repositories and permissions of an installation access token.
+///
+/// The default request has no restrictions and returns a token with the full access of the
+/// installation. Tokens are cached per request, so a restricted token is never handed out for a
+/// request that needs more access, and vice versa.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Deserialize, Serialize)]
+pub struct TokenRequest {
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    repositories: BTreeSet<RepositoryName>,
+
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    repository_ids: BTreeSet<RepositoryId>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    permissions: BTreeMap<Permission, PermissionLevel>,
+}
+
+impl TokenRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn repository(mut self, repository: RepositoryName) -> Self {
+        self.repositories.insert(repository);
+        self
+    }
+
+    pub fn repository_id(mut self, repository_id: RepositoryId) -> Self {
+        self.repository_ids.insert(repository_id);
+        self
+    }
+
+    pub fn permission(mut self, permission: Permission, level: PermissionLevel) -> Self {
+        self.permissions.insert(permission, level);
+        self
+    }
+
+    pub fn repositories(&self) -> &BTreeSet<RepositoryName> {
+        &self.repositories
+    }
+
+    pub fn repository_ids(&self) -> &BTreeSet<RepositoryId> {
+        &self.repository_ids
+    }
+
+    pub fn permissions(&self) -> &BTreeMap<Permission, PermissionLevel> {
+        &self.permissions
+    }
+
+    pub fn is_full_access(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::repository::{RepositoryId, RepositoryName};
+
+    use super::{Permission, PermissionLevel, TokenRequest};
+
+    #[test]
+    fn default_is_full_access() {
+        assert!(TokenRequest::default().is_full_access());
+    }
+
+    #[test]
+    fn trait_serialize() {
+        let request = TokenRequest::new()
+            .repository(RepositoryName::new("github-parts"))
+            .repository_id(RepositoryId::new(496534847))
+            .permission(Permission::new("checks"), PermissionLevel::Write)
+            .permission(Permission::new("contents"), PermissionLevel::Read);
+
+        let json = serde_json::to_string(&request).unwrap();
+
+        assert_eq!(
+            r#"{"repositories":["github-parts"],"repository_ids":[496534847],"permissions":{"checks":"write","contents":"read"}}"#,
+            json
+        );
+    }
+
+    #[test]
+    fn trait_serialize_skips_empty_restrictions() {
+        let request = TokenRequest::new().repository_id(RepositoryId::new(1));
+
+        let json = serde_json::to_string(&request).unwrap();
+
+        assert_eq!(r#"{"repository_ids":[1]}"#, json);
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<TokenRequest>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<TokenRequest>();
+    }
+}
@@ -0,0 +1,170 @@
+use std::fmt::Debug;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use base64::alphabet::URL_SAFE;
+use base64::engine::fast_portable::{FastPortable, NO_PAD};
+use base64::{decode_engine, encode_engine};
+use jsonwebtoken::{crypto, Algorithm, EncodingKey, Header};
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::github::PrivateKey;
+
+const URL_SAFE_NO_PAD: FastPortable = FastPortable::from(&URL_SAFE, NO_PAD);
+
+/// Signs the JSON Web Tokens that authenticate a GitHub App.
+///
+/// GitHub requires the RS256 algorithm. Implementations receive the encoded header and claims of
+/// the token and return the raw signature, which makes it possible to keep the private key in an
+/// HSM or a key management service instead of in memory.
+#[async_trait]
+pub trait JwtSigner: Debug + Send + Sync {
+    async fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+/// Signs tokens with a private key that is held in memory.
+#[derive(Clone, Debug)]
+pub struct PrivateKeySigner {
+    private_key: PrivateKey,
+}
+
+impl PrivateKeySigner {
+    pub fn new(private_key: PrivateKey) -> Self {
+        Self { private_key }
+    }
+}
+
+#[async_trait]
+impl JwtSigner for PrivateKeySigner {
+    #[tracing::instrument(skip(message))]
+    async fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        let key =
+            EncodingKey::from_rsa_pem(self.private_key.get().as_bytes()).map_err(|error| {
+                Error::Configuration(
+                    Box::new(error),
+                    "failed to create encoding key for GitHub App token".into(),
+                )
+            })?;
+
+        let signature = crypto::sign(message, &key, Algorithm::RS256)
+            .context("failed to sign JWT for GitHub App token")?;
+
+        Ok(decode_engine(signature, &URL_SAFE_NO_PAD)
+            .context("failed to decode signature of GitHub App token")?)
+    }
+}
+
+/// Encodes the claims as an RS256 JSON Web Token and signs it with the signer.
+pub(super) async fn encode<T>(signer: &dyn JwtSigner, claims: &T) -> Result<String, Error>
+where
+    T: Serialize,
+{
+    let header = serde_json::to_vec(&Header::new(Algorithm::RS256))
+        .context("failed to serialize header of GitHub App token")?;
+    let claims =
+        serde_json::to_vec(claims).context("failed to serialize claims of GitHub App token")?;
+
+    let message = format!(
+        "{}.{}",
+        encode_engine(header, &URL_SAFE_NO_PAD),
+        encode_engine(claims, &URL_SAFE_NO_PAD)
+    );
+
+    let signature = signer.sign(message.as_bytes()).await?;
+
+    Ok(format!(
+        "{}.{}",
+        message,
+        encode_engine(signature, &URL_SAFE_NO_PAD)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use async_trait::async_trait;
+    use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+    use serde_json::{json, Value};
+
+    use crate::error::Error;
+    use crate::github::PrivateKey;
+
+    use super::{encode, JwtSigner, PrivateKeySigner};
+
+    /// Stands in for a remote signing service by counting the messages that it signs and
+    /// delegating the actual signature to a key in memory.
+    #[derive(Debug)]
+    struct RemoteSigner {
+        signatures: AtomicUsize,
+        signer: PrivateKeySigner,
+    }
+
+    #[async_trait]
+    impl JwtSigner for RemoteSigner {
+        async fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Error> {
+            self.signatures.fetch_add(1, Ordering::SeqCst);
+            self.signer.sign(message).await
+        }
+    }
+
+    fn private_key() -> PrivateKey {
+        PrivateKey::new(include_str!("../../../tests/fixtures/private-key.pem").into())
+    }
+
+    #[tokio::test]
+    async fn encode_creates_jwt_that_verifies_with_public_key() {
+        let signer = RemoteSigner {
+            signatures: AtomicUsize::new(0),
+            signer: PrivateKeySigner::new(private_key()),
+        };
+        let claims = json!({ "iss": "1", "iat": 0, "exp": 4102444800u64 });
+
+        let jwt = encode(&signer, &claims).await.unwrap();
+
+        let key =
+            DecodingKey::from_rsa_pem(include_bytes!("../../../tests/fixtures/public-key.pem"))
+                .unwrap();
+        let token = decode::<Value>(&jwt, &key, &Validation::new(Algorithm::RS256)).unwrap();
+
+        assert_eq!(1, signer.signatures.load(Ordering::SeqCst));
+        assert_eq!("1", token.claims["iss"]);
+    }
+
+    #[tokio::test]
+    async fn encode_matches_jsonwebtoken() {
+        let signer = PrivateKeySigner::new(private_key());
+        let claims = json!({ "iss": "1", "iat": 0, "exp": 4102444800u64 });
+
+        let jwt = encode(&signer, &claims).await.unwrap();
+
+        let key = jsonwebtoken::EncodingKey::from_rsa_pem(private_key().get().as_bytes()).unwrap();
+        let expected =
+            jsonwebtoken::encode(&jsonwebtoken::Header::new(Algorithm::RS256), &claims, &key)
+                .unwrap();
+
+        assert_eq!(expected, jwt);
+    }
+
+    #[tokio::test]
+    async fn sign_fails_with_invalid_private_key() {
+        let signer = PrivateKeySigner::new(PrivateKey::new("not a key".into()));
+
+        let error = signer.sign(b"message").await.unwrap_err();
+
+        assert!(matches!(error, Error::Configuration(_, _)));
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<PrivateKeySigner>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<PrivateKeySigner>();
+    }
+}
@@ -1,9 +1,10 @@
 use std::fmt::{Display, Formatter};
 
 use derive_new::new;
-use getset::CopyGetters;
+use getset::{CopyGetters, Getters};
 use serde::{Deserialize, Serialize};
 
+use crate::account::Account;
 use crate::id;
 
 id!(InstallationId);
@@ -33,9 +34,80 @@ impl Display for Installation {
     }
 }
 
+/// An installation as returned by the REST API of GitHub Apps, which, unlike the webhook
+/// payloads, also includes the user or organization that installed the app.
+#[derive(
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Debug,
+    Deserialize,
+    Serialize,
+    CopyGetters,
+    Getters,
+    new,
+)]
+pub struct AppInstallation {
+    #[getset(get_copy = "pub")]
+    id: InstallationId,
+
+    #[getset(get = "pub")]
+    #[serde(default)]
+    account: Option<Account>,
+}
+
+impl AppInstallation {
+    pub fn installation(&self) -> Installation {
+        Installation::new(self.id)
+    }
+}
+
+impl Display for AppInstallation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Installation, InstallationId};
+    use super::{AppInstallation, Installation, InstallationId};
+
+    #[test]
+    fn app_installation_trait_deserialize() {
+        let json = r#"
+        {
+            "id": 1,
+            "account": {
+                "login": "octocat",
+                "id": 1,
+                "type": "User"
+            },
+            "app_id": 1,
+            "target_type": "User"
+        }
+        "#;
+
+        let installation: AppInstallation = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            "octocat",
+            installation.account().as_ref().unwrap().login().get()
+        );
+        assert_eq!(
+            Installation::new(InstallationId::new(1)),
+            installation.installation()
+        );
+    }
+
+    #[test]
+    fn app_installation_trait_deserialize_without_account() {
+        let installation: AppInstallation = serde_json::from_str(r#"{ "id": 1 }"#).unwrap();
+
+        assert!(installation.account().is_none());
+    }
 
     #[test]
     fn trait_display() {
@@ -12,3 +12,4 @@ pub mod installation;
 pub mod repository;
 pub mod testing;
 pub mod visibility;
+pub mod webhook;
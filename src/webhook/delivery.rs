@@ -0,0 +1,272 @@
+use std::fmt::{Display, Formatter};
+
+use getset::{CopyGetters, Getters};
+use reqwest::header::{HeaderMap, USER_AGENT};
+use serde::{Deserialize, Serialize};
+
+use crate::event::Event;
+use crate::webhook::{SignatureError, WebhookVerifier};
+use crate::{id, name};
+
+const EVENT_HEADER: &str = "x-github-event";
+const DELIVERY_HEADER: &str = "x-github-delivery";
+const HOOK_ID_HEADER: &str = "x-github-hook-id";
+const INSTALLATION_TARGET_ID_HEADER: &str = "x-github-hook-installation-target-id";
+const INSTALLATION_TARGET_TYPE_HEADER: &str = "x-github-hook-installation-target-type";
+
+name!(DeliveryId);
+id!(HookId);
+
+/// A webhook delivery from GitHub: the event and the metadata from the headers of the request.
+#[derive(Clone, Eq, PartialEq, Debug, CopyGetters, Getters)]
+pub struct Delivery {
+    /// The GUID that identifies the delivery, which can be used to deduplicate redeliveries.
+    #[getset(get = "pub")]
+    delivery_id: DeliveryId,
+
+    #[getset(get_copy = "pub")]
+    hook_id: Option<HookId>,
+
+    /// The id of the resource where the webhook was created, e.g. a repository or a GitHub App.
+    #[getset(get_copy = "pub")]
+    installation_target_id: Option<u64>,
+
+    /// The type of the resource where the webhook was created, e.g. `repository` or
+    /// `integration`.
+    #[getset(get = "pub")]
+    installation_target_type: Option<String>,
+
+    #[getset(get = "pub")]
+    user_agent: Option<String>,
+
+    #[getset(get = "pub")]
+    event: Event,
+}
+
+impl Delivery {
+    pub fn into_event(self) -> Event {
+        self.event
+    }
+}
+
+/// Verifies webhook deliveries and turns them into events.
+#[derive(Clone, Debug)]
+pub struct WebhookParser {
+    verifier: WebhookVerifier,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    #[error(transparent)]
+    Signature(#[from] SignatureError),
+
+    #[error("webhook has no {0} header")]
+    MissingHeader(&'static str),
+
+    #[error("webhook has a malformed {0} header")]
+    MalformedHeader(&'static str),
+
+    #[error("failed to deserialize payload of {0} event")]
+    Payload(String, #[source] serde_json::Error),
+}
+
+impl WebhookParser {
+    pub fn new(verifier: WebhookVerifier) -> Self {
+        Self { verifier }
+    }
+
+    /// Verifies the signature of a delivery and parses its body into the event that is named in
+    /// the `X-GitHub-Event` header.
+    ///
+    /// Events that are not modelled yet are returned as [`Event::Unsupported`].
+    #[tracing::instrument(skip(body))]
+    pub fn parse(&self, headers: &HeaderMap, body: &[u8]) -> Result<Delivery, WebhookError> {
+        self.verifier.verify(headers, body)?;
+
+        let event_name =
+            header(headers, EVENT_HEADER)?.ok_or(WebhookError::MissingHeader(EVENT_HEADER))?;
+        let delivery_id = header(headers, DELIVERY_HEADER)?
+            .ok_or(WebhookError::MissingHeader(DELIVERY_HEADER))?;
+
+        let event = parse_event(event_name, body)
+            .map_err(|error| WebhookError::Payload(event_name.into(), error))?;
+
+        Ok(Delivery {
+            delivery_id: DeliveryId::new(delivery_id),
+            hook_id: id_header(headers, HOOK_ID_HEADER)?.map(HookId::new),
+            installation_target_id: id_header(headers, INSTALLATION_TARGET_ID_HEADER)?,
+            installation_target_type: header(headers, INSTALLATION_TARGET_TYPE_HEADER)?
+                .map(String::from),
+            user_agent: header(headers, USER_AGENT.as_str())?.map(String::from),
+            event,
+        })
+    }
+}
+
+fn parse_event(event_name: &str, body: &[u8]) -> Result<Event, serde_json::Error> {
+    let event = match event_name {
+        "check_run" => Event::CheckRun(Box::new(serde_json::from_slice(body)?)),
+        _ => Event::Unsupported(event_name.into(), serde_json::from_slice(body)?),
+    };
+
+    Ok(event)
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &'static str) -> Result<Option<&'a str>, WebhookError> {
+    headers
+        .get(name)
+        .map(|value| {
+            value
+                .to_str()
+                .map_err(|_| WebhookError::MalformedHeader(name))
+        })
+        .transpose()
+}
+
+fn id_header(headers: &HeaderMap, name: &'static str) -> Result<Option<u64>, WebhookError> {
+    header(headers, name)?
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| WebhookError::MalformedHeader(name))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read;
+
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use ring::hmac;
+
+    use crate::event::Event;
+    use crate::github::WebhookSecret;
+    use crate::webhook::{SignatureError, WebhookVerifier};
+
+    use super::{Delivery, WebhookError, WebhookParser};
+
+    const SECRET: &str = "It's a Secret to Everybody";
+
+    fn parser() -> WebhookParser {
+        WebhookParser::new(WebhookVerifier::new(WebhookSecret::new(SECRET.into())))
+    }
+
+    fn headers(event: &str, body: &[u8]) -> HeaderMap {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, SECRET.as_bytes());
+        let signature: String = hmac::sign(&key, body)
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+
+        let mut headers = HeaderMap::new();
+        let mut insert = |name: &'static str, value: &str| {
+            headers.insert(name, HeaderValue::from_str(value).unwrap());
+        };
+
+        insert("x-github-event", event);
+        insert("x-github-delivery", "72d3162e-cc78-11e3-81ab-4c9367dc0958");
+        insert("x-github-hook-id", "292430182");
+        insert("x-github-hook-installation-target-id", "79929171");
+        insert("x-github-hook-installation-target-type", "integration");
+        insert("user-agent", "GitHub-Hookshot/044aadd");
+        insert("x-hub-signature-256", &format!("sha256={}", signature));
+
+        headers
+    }
+
+    #[test]
+    fn parse_returns_check_run_event() {
+        let fixture = format!(
+            "{}/tests/fixtures/check_run.created.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let body = read(fixture).unwrap();
+
+        let delivery: Delivery = parser().parse(&headers("check_run", &body), &body).unwrap();
+
+        assert_eq!(
+            "72d3162e-cc78-11e3-81ab-4c9367dc0958",
+            delivery.delivery_id().get()
+        );
+        assert_eq!(292430182, delivery.hook_id().unwrap().get());
+        assert_eq!(Some(79929171), delivery.installation_target_id());
+        assert_eq!(
+            Some("integration"),
+            delivery.installation_target_type().as_deref()
+        );
+        assert_eq!(
+            Some("GitHub-Hookshot/044aadd"),
+            delivery.user_agent().as_deref()
+        );
+        assert!(matches!(delivery.into_event(), Event::CheckRun(_)));
+    }
+
+    #[test]
+    fn parse_keeps_name_of_unsupported_event() {
+        let body = br#"{ "zen": "Keep it logically awesome." }"#;
+
+        let delivery = parser().parse(&headers("ping", body), body).unwrap();
+
+        match delivery.event() {
+            Event::Unsupported(name, payload) => {
+                assert_eq!("ping", name);
+                assert_eq!("Keep it logically awesome.", payload["zen"]);
+            }
+            event => panic!("unexpected event {}", event),
+        }
+    }
+
+    #[test]
+    fn parse_verifies_signature() {
+        let body = br#"{ "zen": "Keep it logically awesome." }"#;
+        let headers = headers("ping", body);
+
+        let error = parser()
+            .parse(&headers, br#"{ "zen": "Forged." }"#)
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            WebhookError::Signature(SignatureError::Mismatch)
+        ));
+    }
+
+    #[test]
+    fn parse_requires_event_header() {
+        let body = b"{}";
+        let mut headers = headers("ping", body);
+        headers.remove("x-github-event");
+
+        let error = parser().parse(&headers, body).unwrap_err();
+
+        assert!(matches!(
+            error,
+            WebhookError::MissingHeader("x-github-event")
+        ));
+    }
+
+    #[test]
+    fn parse_fails_for_invalid_payload() {
+        let body = br#"{ "action": "created" }"#;
+
+        let error = parser()
+            .parse(&headers("check_run", body), body)
+            .unwrap_err();
+
+        assert!(matches!(error, WebhookError::Payload(name, _) if name == "check_run"));
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Delivery>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<Delivery>();
+    }
+}
@@ -0,0 +1,5 @@
+pub use self::delivery::{Delivery, DeliveryId, HookId, WebhookError, WebhookParser};
+pub use self::signature::{SignatureError, WebhookVerifier};
+
+mod delivery;
+mod signature;
@@ -0,0 +1,221 @@
+use reqwest::header::HeaderMap;
+use ring::hmac;
+
+use crate::github::WebhookSecret;
+
+const SIGNATURE_256_HEADER: &str = "x-hub-signature-256";
+const SIGNATURE_HEADER: &str = "x-hub-signature";
+
+/// Verifies that a webhook was sent by GitHub by checking its signature against the raw body.
+///
+/// Several secrets can be configured while a secret is rotated. A delivery is accepted if it was
+/// signed with any of them.
+#[derive(Clone, Debug)]
+pub struct WebhookVerifier {
+    secrets: Vec<WebhookSecret>,
+    sha1_fallback: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SignatureError {
+    #[error("webhook has no {0} header")]
+    Missing(&'static str),
+
+    #[error("webhook has a malformed {0} header")]
+    Malformed(&'static str),
+
+    #[error("webhook signature does not match any secret")]
+    Mismatch,
+}
+
+impl WebhookVerifier {
+    pub fn new(secret: WebhookSecret) -> Self {
+        Self::with_secrets(vec![secret])
+    }
+
+    /// Creates a verifier that accepts deliveries signed with any of the secrets.
+    pub fn with_secrets(secrets: Vec<WebhookSecret>) -> Self {
+        Self {
+            secrets,
+            sha1_fallback: false,
+        }
+    }
+
+    /// Accepts the legacy `X-Hub-Signature` header with an HMAC-SHA1 signature if a delivery
+    /// has no `X-Hub-Signature-256` header, e.g. from an old GitHub Enterprise Server.
+    pub fn with_sha1_fallback(mut self, sha1_fallback: bool) -> Self {
+        self.sha1_fallback = sha1_fallback;
+        self
+    }
+
+    /// Checks the signature of a delivery against its raw body.
+    ///
+    /// The body must be verified exactly as it was received, before it is deserialized.
+    #[tracing::instrument(skip(body))]
+    pub fn verify(&self, headers: &HeaderMap, body: &[u8]) -> Result<(), SignatureError> {
+        if headers.contains_key(SIGNATURE_256_HEADER) {
+            return self.verify_header(
+                headers,
+                body,
+                SIGNATURE_256_HEADER,
+                "sha256=",
+                hmac::HMAC_SHA256,
+            );
+        }
+
+        // The SHA-1 header is only read if the SHA-256 header is missing, so that a delivery
+        // cannot be downgraded to the weaker signature.
+        if self.sha1_fallback && headers.contains_key(SIGNATURE_HEADER) {
+            return self.verify_header(
+                headers,
+                body,
+                SIGNATURE_HEADER,
+                "sha1=",
+                hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+            );
+        }
+
+        Err(SignatureError::Missing(SIGNATURE_256_HEADER))
+    }
+
+    fn verify_header(
+        &self,
+        headers: &HeaderMap,
+        body: &[u8],
+        header: &'static str,
+        prefix: &str,
+        algorithm: hmac::Algorithm,
+    ) -> Result<(), SignatureError> {
+        let signature = headers
+            .get(header)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix(prefix))
+            .and_then(decode_hex)
+            .filter(|signature| signature.len() == algorithm.digest_algorithm().output_len)
+            .ok_or(SignatureError::Malformed(header))?;
+
+        // `hmac::verify` compares the signatures in constant time.
+        let is_valid = self.secrets.iter().any(|secret| {
+            let key = hmac::Key::new(algorithm, secret.get().as_bytes());
+            hmac::verify(&key, body, &signature).is_ok()
+        });
+
+        if is_valid {
+            Ok(())
+        } else {
+            Err(SignatureError::Mismatch)
+        }
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 == 1 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    use crate::github::WebhookSecret;
+
+    use super::{SignatureError, WebhookVerifier};
+
+    const BODY: &[u8] = b"Hello, World!";
+    const SIGNATURE_256: &str =
+        "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+    const SIGNATURE: &str = "sha1=01dc10d0c83e72ed246219cdd91669667fe2ca59";
+
+    fn secret() -> WebhookSecret {
+        WebhookSecret::new("It's a Secret to Everybody".into())
+    }
+
+    fn headers(name: &'static str, value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn verify_accepts_valid_signature() {
+        let verifier = WebhookVerifier::new(secret());
+
+        let result = verifier.verify(&headers("x-hub-signature-256", SIGNATURE_256), BODY);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn verify_accepts_any_secret() {
+        let verifier =
+            WebhookVerifier::with_secrets(vec![WebhookSecret::new("new-secret".into()), secret()]);
+
+        let result = verifier.verify(&headers("x-hub-signature-256", SIGNATURE_256), BODY);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn verify_rejects_modified_body() {
+        let verifier = WebhookVerifier::new(secret());
+
+        let result = verifier.verify(
+            &headers("x-hub-signature-256", SIGNATURE_256),
+            b"Goodbye, World!",
+        );
+
+        assert!(matches!(result, Err(SignatureError::Mismatch)));
+    }
+
+    #[test]
+    fn verify_rejects_missing_signature() {
+        let verifier = WebhookVerifier::new(secret());
+
+        let result = verifier.verify(&HeaderMap::new(), BODY);
+
+        assert!(matches!(result, Err(SignatureError::Missing(_))));
+    }
+
+    #[test]
+    fn verify_rejects_malformed_signature() {
+        let verifier = WebhookVerifier::new(secret());
+
+        for signature in ["757107ea", "sha256=zz", "sha256=757107ea"] {
+            let result = verifier.verify(&headers("x-hub-signature-256", signature), BODY);
+
+            assert!(matches!(result, Err(SignatureError::Malformed(_))));
+        }
+    }
+
+    #[test]
+    fn verify_falls_back_to_sha1_if_enabled() {
+        let headers = headers("x-hub-signature", SIGNATURE);
+
+        let strict = WebhookVerifier::new(secret());
+        assert!(matches!(
+            strict.verify(&headers, BODY),
+            Err(SignatureError::Missing(_))
+        ));
+
+        let legacy = WebhookVerifier::new(secret()).with_sha1_fallback(true);
+        assert!(legacy.verify(&headers, BODY).is_ok());
+    }
+
+    #[test]
+    fn trait_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<WebhookVerifier>();
+    }
+
+    #[test]
+    fn trait_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<WebhookVerifier>();
+    }
+}